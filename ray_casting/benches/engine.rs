@@ -0,0 +1,134 @@
+// Benchmarks del motor: `cargo bench`. Los mapas se generan aquí a distintos tamaños para no
+// depender de archivos; las semillas son fijas, así que cada corrida mide exactamente lo mismo.
+use std::hint::black_box;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use nalgebra_glm::Vec2;
+use ray_casting::assets::{checkerboard, Map};
+use ray_casting::bmp::{write_bmp, BmpFormat};
+use ray_casting::caster::cast_ray;
+use ray_casting::color::Color;
+use ray_casting::framebuffer::FrameBuffer;
+use ray_casting::player::Player;
+use ray_casting::render::{block_size, render3d, render3d_threaded};
+
+// Lados de los mapas generados, en celdas
+const MAP_SIZES: [usize; 3] = [10, 25, 50];
+const RESOLUTIONS: [(usize, usize); 3] = [(320, 240), (640, 480), (1000, 900)];
+// Hilos fijos para que las mediciones se puedan comparar entre máquinas
+const RENDER_THREADS: usize = 4;
+
+// Sala vacía de `size` x `size` rodeada de paredes
+fn open_map(size: usize) -> Map {
+    (0..size)
+        .map(|row| {
+            (0..size)
+                .map(|col| {
+                    let edge_row = row == 0 || row == size - 1;
+                    let edge_col = col == 0 || col == size - 1;
+                    match (edge_row, edge_col) {
+                        (true, true) => '+',
+                        (true, false) => '-',
+                        (false, true) => '|',
+                        (false, false) => ' ',
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+// La misma sala con columnas y paredes sueltas en un tercio de las celdas interiores.
+// La celda (1, 1), donde empieza el jugador, queda libre.
+fn cluttered_map(size: usize) -> Map {
+    let mut map = open_map(size);
+    let mut seed: u32 = 0x2545_F491;
+    for (row, cells) in map.iter_mut().enumerate().take(size - 1).skip(1) {
+        for (col, cell) in cells.iter_mut().enumerate().take(size - 1).skip(1) {
+            // Generador congruencial: suficiente para repartir paredes de forma reproducible
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            if (row, col) != (1, 1) && seed >> 16 < u16::MAX as u32 / 3 {
+                *cell = if (seed >> 8) & 1 == 0 { '|' } else { '-' };
+            }
+        }
+    }
+    map
+}
+
+// Jugador en el centro de la celda (1, 1), mirando hacia el interior del mapa
+fn player_for(map: &Map) -> Player {
+    let block = block_size(map) as f32;
+    Player::new(Vec2::new(block * 1.5, block * 1.5), 0.6)
+}
+
+fn bench_cast_ray(c: &mut Criterion) {
+    let mut group = c.benchmark_group("cast_ray");
+    for size in MAP_SIZES {
+        for (layout, map) in [("open", open_map(size)), ("cluttered", cluttered_map(size))] {
+            let player = player_for(&map);
+            let block = block_size(&map);
+            group.bench_with_input(BenchmarkId::new(layout, size), &map, |b, map| {
+                b.iter(|| cast_ray(black_box(map), &player, black_box(player.a), block))
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bench_render3d(c: &mut Criterion) {
+    let mut group = c.benchmark_group("render3d");
+    group.sample_size(30);
+    let map = cluttered_map(25);
+    let player = player_for(&map);
+    let texture = checkerboard();
+    for (width, height) in RESOLUTIONS {
+        let resolution = format!("{}x{}", width, height);
+        let mut framebuffer = FrameBuffer::new(width, height);
+        group.throughput(Throughput::Elements((width * height) as u64));
+        group.bench_function(BenchmarkId::new("single", &resolution), |b| {
+            b.iter(|| render3d(&mut framebuffer, black_box(&map), &player, &texture))
+        });
+        group.bench_function(BenchmarkId::new(format!("{}_threads", RENDER_THREADS), &resolution), |b| {
+            b.iter(|| render3d_threaded(&mut framebuffer, black_box(&map), &player, &texture, RENDER_THREADS))
+        });
+    }
+    group.finish();
+}
+
+fn bench_framebuffer(c: &mut Criterion) {
+    let mut group = c.benchmark_group("framebuffer");
+    for (width, height) in RESOLUTIONS {
+        let resolution = format!("{}x{}", width, height);
+        let mut framebuffer = FrameBuffer::new(width, height);
+        framebuffer.set_current_color(Color::new(50, 50, 100));
+        group.throughput(Throughput::Elements((width * height) as u64));
+        group.bench_function(BenchmarkId::new("clear", &resolution), |b| b.iter(|| framebuffer.clear()));
+        group.bench_function(BenchmarkId::new("draw_rectangle", &resolution), |b| {
+            b.iter(|| framebuffer.draw_rectangle(black_box(width / 4), height / 4, width / 2, height / 2))
+        });
+    }
+    group.finish();
+}
+
+fn bench_bmp(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bmp_encode");
+    for (width, height) in RESOLUTIONS {
+        let resolution = format!("{}x{}", width, height);
+        let map = cluttered_map(25);
+        let mut framebuffer = FrameBuffer::new(width, height);
+        render3d(&mut framebuffer, &map, &player_for(&map), &checkerboard());
+        let mut encoded = Vec::with_capacity(width * height * 4 + 256);
+        group.throughput(Throughput::Bytes((width * height * 3) as u64));
+        for (name, format) in [("rgb24", BmpFormat::Rgb24), ("bgra32", BmpFormat::Bgra32)] {
+            group.bench_function(BenchmarkId::new(name, &resolution), |b| {
+                b.iter(|| {
+                    encoded.clear();
+                    write_bmp(&mut encoded, black_box(&framebuffer.buffer), width, height, format).unwrap();
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_cast_ray, bench_render3d, bench_framebuffer, bench_bmp);
+criterion_main!(benches);
@@ -0,0 +1,342 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{BufReader, Cursor};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use image::GrayImage;
+#[cfg(feature = "audio")]
+use rodio::{Decoder, Source};
+#[cfg(not(feature = "audio"))]
+use crate::no_audio::Decoder;
+use rusttype::Font;
+use crate::maze::parse_maze;
+
+// Referencia liviana a un recurso cargado; el tipo evita mezclar, p. ej., texturas con mapas
+pub struct Handle<T> {
+    index: usize,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Handle<T> {
+    fn new(index: usize) -> Handle<T> {
+        Handle { index, marker: PhantomData }
+    }
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Handle<T> {
+        *self
+    }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Handle<T>) -> bool {
+        self.index == other.index
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> std::hash::Hash for Handle<T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+    }
+}
+
+impl<T> fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Handle({})", self.index)
+    }
+}
+
+pub type Texture = GrayImage;
+pub type Map = Vec<Vec<char>>;
+
+// Sonido decodificado en memoria
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sound {
+    pub channels: u16,
+    pub sample_rate: u32,
+    pub samples: Vec<f32>,
+}
+
+impl Sound {
+    // Mezcla todos los canales en uno
+    pub fn to_mono(&self) -> Vec<f32> {
+        let channels = self.channels.max(1) as usize;
+        self.samples
+            .chunks(channels)
+            .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+            .collect()
+    }
+}
+
+#[derive(Debug)]
+pub enum AssetError {
+    // No existe en ninguna de las rutas de búsqueda
+    NotFound { name: PathBuf, searched: Vec<PathBuf> },
+    Io { path: PathBuf, source: std::io::Error },
+    // El archivo existe pero su contenido no es válido
+    Decode { path: PathBuf, message: String },
+}
+
+impl fmt::Display for AssetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AssetError::NotFound { name, searched } => {
+                write!(f, "asset '{}' not found in:", name.display())?;
+                for root in searched {
+                    write!(f, " {}", root.display())?;
+                }
+                Ok(())
+            }
+            AssetError::Io { path, source } => write!(f, "could not read '{}': {}", path.display(), source),
+            AssetError::Decode { path, message } => write!(f, "could not load '{}': {}", path.display(), message),
+        }
+    }
+}
+
+impl std::error::Error for AssetError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AssetError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+// Recursos de un tipo, guardados por handle y por el nombre con el que se pidieron
+struct Store<T> {
+    items: Vec<T>,
+    by_name: HashMap<PathBuf, Handle<T>>,
+    placeholder: Option<Handle<T>>,
+}
+
+impl<T> Store<T> {
+    fn new() -> Store<T> {
+        Store { items: Vec::new(), by_name: HashMap::new(), placeholder: None }
+    }
+
+    fn insert(&mut self, name: Option<&Path>, item: T) -> Handle<T> {
+        let handle = Handle::new(self.items.len());
+        self.items.push(item);
+        if let Some(name) = name {
+            self.by_name.insert(name.to_path_buf(), handle);
+        }
+        handle
+    }
+
+    // Reemplazo compartido por todos los recursos de este tipo que no se pudieron cargar
+    fn placeholder(&mut self, make: impl FnOnce() -> T) -> Handle<T> {
+        if let Some(handle) = self.placeholder {
+            return handle;
+        }
+        let handle = self.insert(None, make());
+        self.placeholder = Some(handle);
+        handle
+    }
+
+    fn get(&self, handle: Handle<T>) -> &T {
+        &self.items[handle.index]
+    }
+}
+
+// Administrador de recursos: busca cada nombre en las rutas configuradas, en orden,
+// carga cada archivo una sola vez y devuelve handles
+pub struct Assets {
+    roots: Vec<PathBuf>,
+    textures: Store<Texture>,
+    maps: Store<Map>,
+    sounds: Store<Sound>,
+    fonts: Store<Font<'static>>,
+}
+
+impl Assets {
+    pub fn new(roots: Vec<PathBuf>) -> Assets {
+        Assets {
+            roots,
+            textures: Store::new(),
+            maps: Store::new(),
+            sounds: Store::new(),
+            fonts: Store::new(),
+        }
+    }
+
+    pub fn roots(&self) -> &[PathBuf] {
+        &self.roots
+    }
+
+    // Primera ruta existente para `name`; las rutas absolutas se usan tal cual
+    pub fn resolve(&self, name: impl AsRef<Path>) -> Result<PathBuf, AssetError> {
+        let name = name.as_ref();
+        if name.is_absolute() {
+            return if name.exists() { Ok(name.to_path_buf()) } else { Err(self.not_found(name)) };
+        }
+        self.roots
+            .iter()
+            .map(|root| root.join(name))
+            .find(|path| path.exists())
+            .ok_or_else(|| self.not_found(name))
+    }
+
+    fn not_found(&self, name: &Path) -> AssetError {
+        let searched = if name.is_absolute() { Vec::new() } else { self.roots.clone() };
+        AssetError::NotFound { name: name.to_path_buf(), searched }
+    }
+
+    fn read(&self, name: &Path) -> Result<(PathBuf, Vec<u8>), AssetError> {
+        let path = self.resolve(name)?;
+        let bytes = std::fs::read(&path).map_err(|source| AssetError::Io { path: path.clone(), source })?;
+        Ok((path, bytes))
+    }
+
+    // Textura en escala de grises
+    pub fn load_texture(&mut self, name: impl AsRef<Path>) -> Result<Handle<Texture>, AssetError> {
+        let name = name.as_ref();
+        if let Some(&handle) = self.textures.by_name.get(name) {
+            return Ok(handle);
+        }
+        let (path, bytes) = self.read(name)?;
+        let image = image::load_from_memory(&bytes)
+            .map_err(|e| AssetError::Decode { path, message: e.to_string() })?;
+        Ok(self.textures.insert(Some(name), image.to_luma8()))
+    }
+
+    pub fn load_map(&mut self, name: impl AsRef<Path>) -> Result<Handle<Map>, AssetError> {
+        let name = name.as_ref();
+        if let Some(&handle) = self.maps.by_name.get(name) {
+            return Ok(handle);
+        }
+        let (path, bytes) = self.read(name)?;
+        let text = String::from_utf8(bytes)
+            .map_err(|_| AssetError::Decode { path: path.clone(), message: "map is not valid UTF-8".into() })?;
+        let maze = parse_maze(&text).map_err(|message| AssetError::Decode { path, message })?;
+        Ok(self.maps.insert(Some(name), maze))
+    }
+
+    pub fn load_sound(&mut self, name: impl AsRef<Path>) -> Result<Handle<Sound>, AssetError> {
+        let name = name.as_ref();
+        if let Some(&handle) = self.sounds.by_name.get(name) {
+            return Ok(handle);
+        }
+        let (path, bytes) = self.read(name)?;
+        let decoder = Decoder::new(BufReader::new(Cursor::new(bytes)))
+            .map_err(|e| AssetError::Decode { path, message: e.to_string() })?;
+        let sound = Sound {
+            channels: decoder.channels(),
+            sample_rate: decoder.sample_rate(),
+            samples: decoder.convert_samples().collect(),
+        };
+        Ok(self.sounds.insert(Some(name), sound))
+    }
+
+    pub fn load_font(&mut self, name: impl AsRef<Path>) -> Result<Handle<Font<'static>>, AssetError> {
+        let name = name.as_ref();
+        if let Some(&handle) = self.fonts.by_name.get(name) {
+            return Ok(handle);
+        }
+        let (path, bytes) = self.read(name)?;
+        let font = Font::try_from_vec(bytes)
+            .ok_or_else(|| AssetError::Decode { path, message: "invalid font data".into() })?;
+        Ok(self.fonts.insert(Some(name), font))
+    }
+
+    // Como `load_texture`, pero si falla avisa y devuelve un tablero de ajedrez
+    pub fn texture_or_placeholder(&mut self, name: impl AsRef<Path>) -> Handle<Texture> {
+        self.load_texture(name).unwrap_or_else(|e| {
+            eprintln!("Warning: {}; using a placeholder texture", e);
+            self.textures.placeholder(checkerboard)
+        })
+    }
+
+    // Como `load_map`, pero si falla avisa y devuelve una sala vacía
+    pub fn map_or_placeholder(&mut self, name: impl AsRef<Path>) -> Handle<Map> {
+        self.load_map(name).unwrap_or_else(|e| {
+            eprintln!("Warning: {}; using a placeholder map", e);
+            self.maps.placeholder(placeholder_map)
+        })
+    }
+
+    pub fn texture(&self, handle: Handle<Texture>) -> &Texture {
+        self.textures.get(handle)
+    }
+
+    pub fn map(&self, handle: Handle<Map>) -> &Map {
+        self.maps.get(handle)
+    }
+
+    pub fn sound(&self, handle: Handle<Sound>) -> &Sound {
+        self.sounds.get(handle)
+    }
+
+    pub fn font(&self, handle: Handle<Font<'static>>) -> &Font<'static> {
+        self.fonts.get(handle)
+    }
+}
+
+// Textura de reemplazo: tablero de 64x64 con casillas de 8 píxeles
+pub fn checkerboard() -> Texture {
+    GrayImage::from_fn(64, 64, |x, y| {
+        let value = if (x / 8 + y / 8) % 2 == 0 { 200 } else { 90 };
+        image::Luma([value])
+    })
+}
+
+// Mapa de reemplazo: una sala cerrada con el jugador en una esquina y la meta en la otra
+fn placeholder_map() -> Map {
+    ["+--+--+--+", "|p       |", "|        |", "|        |", "|       g|", "+--+--+--+"]
+        .iter()
+        .map(|row| row.chars().collect())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixtures() -> Assets {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        Assets::new(vec![root.join("does-not-exist"), root.join("tests/fixtures"), root.to_path_buf()])
+    }
+
+    #[test]
+    fn resolves_names_against_the_search_paths_in_order() {
+        let assets = fixtures();
+        let path = assets.resolve("corridor.txt").unwrap();
+        assert!(path.ends_with("tests/fixtures/corridor.txt"));
+        assert!(assets.resolve("assets/textura.png").unwrap().ends_with("assets/textura.png"));
+    }
+
+    #[test]
+    fn caches_loaded_assets_by_name() {
+        let mut assets = fixtures();
+        let first = assets.load_map("corridor.txt").unwrap();
+        let second = assets.load_map("corridor.txt").unwrap();
+        assert_eq!(first, second);
+        assert_eq!(assets.map(first)[0].len(), 13);
+    }
+
+    #[test]
+    fn reports_typed_errors_and_substitutes_placeholders() {
+        let mut assets = fixtures();
+        assert!(matches!(assets.load_texture("missing.png"), Err(AssetError::NotFound { .. })));
+        assert!(matches!(assets.load_texture("corridor.txt"), Err(AssetError::Decode { .. })));
+
+        let placeholder = assets.texture_or_placeholder("missing.png");
+        assert_eq!(assets.texture(placeholder), &checkerboard());
+        assert_eq!(assets.texture_or_placeholder("also-missing.png"), placeholder);
+
+        // Un mapa más ancho que el mundo dejaría celdas de tamaño 0
+        let dir = std::env::temp_dir().join(format!("ray_casting_wide_map_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("wide.txt"), "+".repeat(1001)).unwrap();
+        let mut wide = Assets::new(vec![dir.clone()]);
+        assert!(matches!(wide.load_map("wide.txt"), Err(AssetError::Decode { .. })));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let map = assets.map_or_placeholder("missing.txt");
+        assert!(assets.map(map).iter().flatten().any(|&cell| cell == 'g'));
+    }
+}
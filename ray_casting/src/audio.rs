@@ -0,0 +1,342 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use nalgebra_glm::Vec2;
+#[cfg(feature = "audio")]
+use rodio::buffer::SamplesBuffer;
+#[cfg(feature = "audio")]
+use rodio::{OutputStream, OutputStreamHandle, Sink};
+#[cfg(not(feature = "audio"))]
+use crate::no_audio::{OutputStream, OutputStreamHandle, SamplesBuffer, Sink};
+use crate::assets::{AssetError, Assets, Sound};
+use crate::music::MusicController;
+use crate::player::Player;
+use crate::positional::{listener_mix, EmitterParams, EmitterSource};
+
+// Cantidad de efectos que pueden sonar a la vez
+const SFX_POOL_SIZE: usize = 8;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Category {
+    Music,
+    Sfx,
+    Ui,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum SoundEffect {
+    Footstep,
+    WallBump,
+    GoalChime,
+    Click,
+    // Sonidos ambientales en bucle, ubicados en el mapa
+    GoalHum,
+    Drip,
+}
+
+impl SoundEffect {
+    const ALL: [SoundEffect; 6] = [
+        SoundEffect::Footstep,
+        SoundEffect::WallBump,
+        SoundEffect::GoalChime,
+        SoundEffect::Click,
+        SoundEffect::GoalHum,
+        SoundEffect::Drip,
+    ];
+
+    pub fn category(self) -> Category {
+        match self {
+            SoundEffect::Click => Category::Ui,
+            _ => Category::Sfx,
+        }
+    }
+
+    fn file_name(self) -> &'static str {
+        match self {
+            SoundEffect::Footstep => "footstep.wav",
+            SoundEffect::WallBump => "wall_bump.wav",
+            SoundEffect::GoalChime => "goal_chime.wav",
+            SoundEffect::Click => "click.wav",
+            SoundEffect::GoalHum => "goal_hum.wav",
+            SoundEffect::Drip => "drip.wav",
+        }
+    }
+
+    // Sonido generado para cuando no existe el archivo del efecto
+    fn synthesize(self) -> Sound {
+        const RATE: u32 = 22_050;
+        let tone = |duration: f32, decay: f32, wave: &mut dyn FnMut(f32) -> f32| -> Vec<f32> {
+            (0..(duration * RATE as f32) as usize)
+                .map(|i| {
+                    let t = i as f32 / RATE as f32;
+                    wave(t) * (-t * decay).exp()
+                })
+                .collect()
+        };
+        let sine = |frequency: f32, t: f32| (t * frequency * std::f32::consts::TAU).sin();
+
+        let samples = match self {
+            // Ruido corto y seco
+            SoundEffect::Footstep => {
+                let mut seed = 0x1234_5678u32;
+                tone(0.08, 60.0, &mut |_| {
+                    seed ^= seed << 13;
+                    seed ^= seed >> 17;
+                    seed ^= seed << 5;
+                    (seed as f32 / u32::MAX as f32 - 0.5) * 0.6
+                })
+            }
+            SoundEffect::WallBump => tone(0.18, 25.0, &mut |t| sine(70.0, t) * 0.9),
+            SoundEffect::GoalChime => tone(0.9, 4.0, &mut |t| (sine(880.0, t) + sine(1320.0, t)) * 0.3),
+            SoundEffect::Click => tone(0.03, 120.0, &mut |t| sine(2000.0, t) * 0.4),
+            // Zumbido grave continuo: un número entero de ciclos para que el bucle no chasquee
+            SoundEffect::GoalHum => tone(1.0, 0.0, &mut |t| {
+                (sine(110.0, t) * 0.5 + sine(220.0, t) * 0.2) * (1.0 + 0.3 * sine(2.0, t)) * 0.4
+            }),
+            // Gota aguda seguida de silencio hasta la siguiente
+            SoundEffect::Drip => tone(1.4, 0.0, &mut |t| {
+                if t < 0.12 { sine(1400.0 - t * 4000.0, t) * (-t * 40.0).exp() * 0.6 } else { 0.0 }
+            }),
+        };
+        Sound { channels: 1, sample_rate: RATE, samples }
+    }
+}
+
+// Dispositivo de salida abierto. Si no existe, `Audio` funciona como backend nulo.
+struct Output {
+    // Debe seguir vivo mientras suene algo
+    _stream: OutputStream,
+    handle: OutputStreamHandle,
+    sfx_pool: Vec<Sink>,
+    next_sink: usize,
+}
+
+// Sonido en bucle atado a una posición del mundo
+struct Emitter {
+    pos: Vec2,
+    category: Category,
+    params: Arc<EmitterParams>,
+    // Debe seguir vivo mientras suene el emisor
+    _sink: Sink,
+}
+
+// Subsistema de audio. Si no hay dispositivo de sonido (contenedores, máquinas sin cabeza)
+// o se desactiva con `--no-audio`, todas las llamadas se ignoran y el juego sigue funcionando.
+pub struct Audio {
+    output: Option<Output>,
+    music: MusicController,
+    clips: HashMap<SoundEffect, Sound>,
+    emitters: Vec<Emitter>,
+    volumes: HashMap<Category, f32>,
+    muted: bool,
+}
+
+impl Audio {
+    pub fn new(enabled: bool) -> Audio {
+        if !enabled {
+            return Audio::disabled();
+        }
+        let (stream, handle) = match OutputStream::try_default() {
+            Ok(output) => output,
+            Err(e) => {
+                eprintln!("Warning: audio disabled, could not open an output device: {}", e);
+                return Audio::disabled();
+            }
+        };
+        let sfx_pool: Vec<Sink> = (0..SFX_POOL_SIZE).filter_map(|_| Sink::try_new(&handle).ok()).collect();
+
+        Audio {
+            music: MusicController::new(Some(handle.clone())),
+            output: Some(Output { _stream: stream, handle, sfx_pool, next_sink: 0 }),
+            ..Audio::disabled()
+        }
+    }
+
+    pub fn disabled() -> Audio {
+        Audio {
+            output: None,
+            music: MusicController::new(None),
+            clips: HashMap::new(),
+            emitters: Vec::new(),
+            volumes: HashMap::new(),
+            muted: false,
+        }
+    }
+
+    pub fn is_available(&self) -> bool {
+        self.output.is_some()
+    }
+
+    // Carga los efectos de `dir` a través del administrador de recursos; los que falten se
+    // reemplazan por sonidos generados
+    pub fn preload_effects(&mut self, assets: &mut Assets, dir: &Path) {
+        if !self.is_available() {
+            return;
+        }
+        for effect in SoundEffect::ALL {
+            let sound = match assets.load_sound(dir.join(effect.file_name())) {
+                Ok(handle) => assets.sound(handle).clone(),
+                Err(AssetError::NotFound { .. }) => effect.synthesize(),
+                Err(e) => {
+                    eprintln!("Warning: {}", e);
+                    effect.synthesize()
+                }
+            };
+            self.clips.insert(effect, sound);
+        }
+    }
+
+    // Reproduce un efecto en el primer canal libre del grupo, o reemplaza el más antiguo
+    pub fn play(&mut self, effect: SoundEffect) {
+        let volume = self.effective_volume(effect.category());
+        let (Some(output), Some(clip)) = (self.output.as_mut(), self.clips.get(&effect)) else {
+            return;
+        };
+        if output.sfx_pool.is_empty() || volume <= 0.0 {
+            return;
+        }
+
+        let index = output
+            .sfx_pool
+            .iter()
+            .position(Sink::empty)
+            .unwrap_or(output.next_sink % output.sfx_pool.len());
+        output.next_sink = (index + 1) % output.sfx_pool.len();
+
+        let sink = &output.sfx_pool[index];
+        sink.stop();
+        sink.set_volume(volume);
+        sink.append(SamplesBuffer::new(clip.channels, clip.sample_rate, clip.samples.clone()));
+        sink.play();
+    }
+
+    // Agrega un sonido en bucle en una posición del mundo; suena en silencio hasta `update_emitters`
+    pub fn add_emitter(&mut self, effect: SoundEffect, pos: Vec2) {
+        let (Some(output), Some(clip)) = (self.output.as_ref(), self.clips.get(&effect)) else {
+            return;
+        };
+        let Ok(sink) = Sink::try_new(&output.handle) else {
+            return;
+        };
+        let params = Arc::new(EmitterParams::default());
+        sink.append(EmitterSource::new(Arc::new(clip.to_mono()), clip.sample_rate, Arc::clone(&params)));
+        sink.play();
+        self.emitters.push(Emitter { pos, category: effect.category(), params, _sink: sink });
+    }
+
+    // Recalcula paneo, distancia y oclusión de cada emisor respecto al jugador
+    // Quita los sonidos ambientales, p. ej. al cambiar de nivel
+    pub fn clear_emitters(&mut self) {
+        self.emitters.clear();
+    }
+
+    pub fn update_emitters(&self, player: &Player, maze: &[Vec<char>], block_size: usize) {
+        for emitter in &self.emitters {
+            let mut mix = listener_mix(player, emitter.pos, maze, block_size);
+            let volume = self.effective_volume(emitter.category);
+            mix.left *= volume;
+            mix.right *= volume;
+            emitter.params.set(mix);
+        }
+    }
+
+    // Cambia la lista de música con un fundido cruzado, p. ej. al cargar otro nivel
+    pub fn play_playlist(&mut self, tracks: Vec<PathBuf>) {
+        if let Err(e) = self.music.play_playlist(tracks) {
+            eprintln!("Warning: could not play music: {}", e);
+        }
+    }
+
+    // Baja el volumen de la música, p. ej. mientras un menú está abierto, y lo recupera al soltarla
+    pub fn set_music_ducked(&mut self, ducked: bool) {
+        self.music.set_ducked(ducked);
+    }
+
+    // Volumen actual de cada lista de música, la que suena primero; también sin dispositivo
+    pub fn music_volumes(&self) -> Vec<f32> {
+        self.music.volumes()
+    }
+
+    // Avanza los fundidos de la música; llamar una vez por cuadro
+    pub fn update(&mut self, dt: f32) {
+        let volume = self.effective_volume(Category::Music);
+        self.music.update(dt, volume);
+    }
+
+    pub fn volume(&self, category: Category) -> f32 {
+        self.volumes.get(&category).copied().unwrap_or(1.0)
+    }
+
+    pub fn set_volume(&mut self, category: Category, volume: f32) {
+        self.volumes.insert(category, volume.clamp(0.0, 1.0));
+    }
+
+    pub fn is_muted(&self) -> bool {
+        self.muted
+    }
+
+    pub fn set_muted(&mut self, muted: bool) {
+        self.muted = muted;
+    }
+
+    pub fn toggle_mute(&mut self) {
+        self.set_muted(!self.muted);
+    }
+
+    fn effective_volume(&self, category: Category) -> f32 {
+        if self.muted { 0.0 } else { self.volume(category) }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_volumes(audio: &Audio, expected: &[f32]) {
+        let volumes = audio.music_volumes();
+        assert_eq!(volumes.len(), expected.len(), "volumes = {:?}", volumes);
+        for (volume, expected) in volumes.iter().zip(expected) {
+            assert!((volume - expected).abs() < 1e-4, "volumes = {:?}, expected {:?}", volumes, expected);
+        }
+    }
+
+    #[test]
+    fn music_crossfades_and_ducks_over_time_without_a_device() {
+        let mut audio = Audio::disabled();
+        audio.play_playlist(vec![PathBuf::from("intro.ogg")]);
+        assert_volumes(&audio, &[0.0]);
+        audio.update(1.0);
+        assert_volumes(&audio, &[0.5]);
+
+        // La lista nueva sube mientras la anterior baja al mismo ritmo; pedir la misma no la reinicia
+        audio.play_playlist(vec![PathBuf::from("level2.ogg")]);
+        audio.play_playlist(vec![PathBuf::from("level2.ogg")]);
+        assert_volumes(&audio, &[0.0, 0.5]);
+        audio.update(0.5);
+        assert_volumes(&audio, &[0.25, 0.25]);
+        audio.update(2.0);
+        assert_volumes(&audio, &[1.0]);
+
+        // La atenuación baja de a poco hasta el 30 % y respeta el volumen de la categoría
+        audio.set_music_ducked(true);
+        audio.update(0.1);
+        assert_volumes(&audio, &[0.7]);
+        audio.update(1.0);
+        assert_volumes(&audio, &[0.3]);
+        audio.set_volume(Category::Music, 0.5);
+        audio.update(0.0);
+        assert_volumes(&audio, &[0.15]);
+        audio.set_muted(true);
+        audio.update(0.0);
+        assert_volumes(&audio, &[0.0]);
+
+        audio.set_muted(false);
+        audio.set_volume(Category::Music, 1.0);
+        audio.set_music_ducked(false);
+        audio.update(0.1);
+        assert_volumes(&audio, &[0.6]);
+        audio.update(1.0);
+        assert_volumes(&audio, &[1.0]);
+    }
+}
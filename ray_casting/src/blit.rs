@@ -0,0 +1,317 @@
+use image::RgbaImage;
+use crate::color::Color;
+use crate::framebuffer::FrameBuffer;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Rect {
+    pub fn new(x: i32, y: i32, width: u32, height: u32) -> Rect {
+        Rect { x, y, width, height }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Filter {
+    Nearest,
+    Bilinear,
+}
+
+impl Filter {
+    pub fn parse(value: &str) -> Option<Filter> {
+        match value.to_ascii_lowercase().as_str() {
+            "nearest" => Some(Filter::Nearest),
+            "bilinear" => Some(Filter::Bilinear),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Transparency {
+    Opaque,
+    // Los píxeles de este color no se dibujan
+    ColorKey(Color),
+    // Mezcla usando el canal alfa de la imagen
+    Alpha,
+}
+
+impl FrameBuffer {
+    // Copia la imagen (o la región `src`) sin escalar, con la esquina superior izquierda en (x, y)
+    pub fn blit(&mut self, image: &RgbaImage, src: Option<Rect>, x: i32, y: i32, transparency: Transparency) {
+        let src = src.unwrap_or(Rect::new(0, 0, image.width(), image.height()));
+        let dst = Rect::new(x, y, src.width, src.height);
+        self.blit_scaled(image, Some(src), dst, Filter::Nearest, transparency);
+    }
+
+    // Dibuja la región `src` de la imagen estirada sobre el rectángulo `dst` del búfer
+    pub fn blit_scaled(
+        &mut self,
+        image: &RgbaImage,
+        src: Option<Rect>,
+        dst: Rect,
+        filter: Filter,
+        transparency: Transparency,
+    ) {
+        let requested = src.unwrap_or(Rect::new(0, 0, image.width(), image.height()));
+        let Some(src) = clip_source(image, requested) else {
+            return;
+        };
+        // La parte de `src` que cae fuera de la imagen se descarta junto con la de `dst` que le
+        // corresponde, en vez de estirar lo que queda sobre todo el destino
+        let dst = if src == requested {
+            dst
+        } else {
+            let scale_x = dst.width as f64 / requested.width as f64;
+            let scale_y = dst.height as f64 / requested.height as f64;
+            let x0 = dst.x as f64 + (src.x as i64 - requested.x as i64) as f64 * scale_x;
+            let y0 = dst.y as f64 + (src.y as i64 - requested.y as i64) as f64 * scale_y;
+            let x1 = x0 + src.width as f64 * scale_x;
+            let y1 = y0 + src.height as f64 * scale_y;
+            let (x0, y0) = (x0.round() as i32, y0.round() as i32);
+            let size = |end: f64, start: i32| (end.round() as i64 - start as i64).clamp(0, u32::MAX as i64) as u32;
+            Rect::new(x0, y0, size(x1, x0), size(y1, y0))
+        };
+        if dst.width == 0 || dst.height == 0 {
+            return;
+        }
+
+        // Recorta el destino al búfer
+        let x_start = dst.x.max(0);
+        let y_start = dst.y.max(0);
+        let x_end = (dst.x as i64 + dst.width as i64).min(self.width as i64) as i32;
+        let y_end = (dst.y as i64 + dst.height as i64).min(self.height as i64) as i32;
+        if x_start >= x_end || y_start >= y_end {
+            return;
+        }
+
+        let scale_x = src.width as f32 / dst.width as f32;
+        let scale_y = src.height as f32 / dst.height as f32;
+
+        // Desplazamientos en i64: con `dst` muy a la izquierda o arriba, `x - dst.x` no entra en un i32
+        for y in y_start..y_end {
+            // Coordenada en la imagen del centro del píxel de destino
+            let v = src.y as f32 + (y as i64 - dst.y as i64) as f32 * scale_y + scale_y / 2.0;
+            let row = self.width * y as usize;
+
+            for x in x_start..x_end {
+                let u = src.x as f32 + (x as i64 - dst.x as i64) as f32 * scale_x + scale_x / 2.0;
+                let (color, alpha) = match filter {
+                    Filter::Nearest => texel(image, &src, u.floor() as i32, v.floor() as i32, transparency),
+                    Filter::Bilinear => sample_bilinear(image, &src, u - 0.5, v - 0.5, transparency),
+                };
+
+                if alpha <= 0.0 {
+                    continue;
+                }
+                let index = row + x as usize;
+                self.buffer[index] = if alpha >= 1.0 {
+                    color.to_hex()
+                } else {
+                    Color::from_hex(self.buffer[index]).lerp(color, alpha).to_hex()
+                };
+            }
+        }
+    }
+}
+
+// Ajusta la región de origen a los límites de la imagen; None si queda vacía
+fn clip_source(image: &RgbaImage, src: Rect) -> Option<Rect> {
+    let x0 = src.x.max(0) as i64;
+    let y0 = src.y.max(0) as i64;
+    let x1 = (src.x as i64 + src.width as i64).min(image.width() as i64);
+    let y1 = (src.y as i64 + src.height as i64).min(image.height() as i64);
+    if x0 >= x1 || y0 >= y1 {
+        return None;
+    }
+    Some(Rect::new(x0 as i32, y0 as i32, (x1 - x0) as u32, (y1 - y0) as u32))
+}
+
+// Lee un texel dentro de `src` (con las coordenadas fijadas al borde) y su opacidad en [0, 1]
+fn texel(image: &RgbaImage, src: &Rect, x: i32, y: i32, transparency: Transparency) -> (Color, f32) {
+    let x = x.clamp(src.x, src.x + src.width as i32 - 1) as u32;
+    let y = y.clamp(src.y, src.y + src.height as i32 - 1) as u32;
+    let [r, g, b, a] = image.get_pixel(x, y).0;
+    let color = Color::new(r, g, b);
+
+    let alpha = match transparency {
+        Transparency::Opaque => 1.0,
+        Transparency::ColorKey(key) => if color == key { 0.0 } else { 1.0 },
+        Transparency::Alpha => a as f32 / 255.0,
+    };
+    (color, alpha)
+}
+
+// Interpolación bilineal con alfa premultiplicado, para que los texels transparentes no tiñan los bordes
+fn sample_bilinear(image: &RgbaImage, src: &Rect, u: f32, v: f32, transparency: Transparency) -> (Color, f32) {
+    let (x0, y0) = (u.floor() as i32, v.floor() as i32);
+    let (fx, fy) = (u - u.floor(), v - v.floor());
+
+    let mut sum = [0.0f32; 3];
+    let mut alpha = 0.0;
+    for (dx, dy, weight) in [
+        (0, 0, (1.0 - fx) * (1.0 - fy)),
+        (1, 0, fx * (1.0 - fy)),
+        (0, 1, (1.0 - fx) * fy),
+        (1, 1, fx * fy),
+    ] {
+        let (color, a) = texel(image, src, x0 + dx, y0 + dy, transparency);
+        let w = weight * a;
+        sum[0] += color.r as f32 * w;
+        sum[1] += color.g as f32 * w;
+        sum[2] += color.b as f32 * w;
+        alpha += w;
+    }
+
+    if alpha <= 0.0 {
+        return (Color::new(0, 0, 0), 0.0);
+    }
+    let channel = |c: f32| (c / alpha).round().clamp(0.0, 255.0) as u8;
+    (Color::new(channel(sum[0]), channel(sum[1]), channel(sum[2])), alpha)
+}
+
+impl FrameBuffer {
+    // Escala el búfer completo para cubrir `target`, p. ej. de la resolución interna a la de la ventana
+    pub fn upscale_into(&self, target: &mut FrameBuffer, filter: Filter) {
+        if self.width == 0 || self.height == 0 {
+            return;
+        }
+        let scale_x = self.width as f32 / target.width as f32;
+        let scale_y = self.height as f32 / target.height as f32;
+
+        match filter {
+            Filter::Nearest => {
+                let columns: Vec<usize> = (0..target.width)
+                    .map(|x| (((x as f32 + 0.5) * scale_x) as usize).min(self.width - 1))
+                    .collect();
+                for y in 0..target.height {
+                    let src_y = (((y as f32 + 0.5) * scale_y) as usize).min(self.height - 1);
+                    let src_row = &self.buffer[src_y * self.width..(src_y + 1) * self.width];
+                    let dst_row = &mut target.buffer[y * target.width..(y + 1) * target.width];
+                    for (pixel, &src_x) in dst_row.iter_mut().zip(&columns) {
+                        *pixel = src_row[src_x];
+                    }
+                }
+            }
+            Filter::Bilinear => {
+                for y in 0..target.height {
+                    let v = ((y as f32 + 0.5) * scale_y - 0.5).clamp(0.0, (self.height - 1) as f32);
+                    let (y0, fy) = (v as usize, v.fract());
+                    let y1 = (y0 + 1).min(self.height - 1);
+
+                    for x in 0..target.width {
+                        let u = ((x as f32 + 0.5) * scale_x - 0.5).clamp(0.0, (self.width - 1) as f32);
+                        let (x0, fx) = (u as usize, u.fract());
+                        let x1 = (x0 + 1).min(self.width - 1);
+
+                        let pixel = |px: usize, py: usize| Color::from_hex(self.buffer[py * self.width + px]);
+                        let top = pixel(x0, y0).lerp(pixel(x1, y0), fx);
+                        let bottom = pixel(x0, y1).lerp(pixel(x1, y1), fx);
+                        target.buffer[y * target.width + x] = top.lerp(bottom, fy).to_hex();
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    const RED: Color = Color { r: 255, g: 0, b: 0 };
+    const BLUE: Color = Color { r: 0, g: 0, b: 255 };
+
+    // Imagen 2x2: rojo, verde / azul, blanco
+    fn quad() -> RgbaImage {
+        RgbaImage::from_fn(2, 2, |x, y| match (x, y) {
+            (0, 0) => Rgba([255, 0, 0, 255]),
+            (1, 0) => Rgba([0, 255, 0, 255]),
+            (0, 1) => Rgba([0, 0, 255, 255]),
+            _ => Rgba([255, 255, 255, 255]),
+        })
+    }
+
+    fn black_framebuffer(width: usize, height: usize) -> FrameBuffer {
+        let mut framebuffer = FrameBuffer::new(width, height);
+        framebuffer.set_background_color(Color::new(0, 0, 0));
+        framebuffer.clear();
+        framebuffer
+    }
+
+    #[test]
+    fn oversized_source_is_clipped_not_stretched() {
+        let mut framebuffer = black_framebuffer(4, 4);
+        framebuffer.blit(&quad(), Some(Rect::new(0, 0, 4, 4)), 0, 0, Transparency::Opaque);
+        assert_eq!(framebuffer.get_color(0, 0), RED);
+        assert_eq!(framebuffer.get_color(1, 1), Color::new(255, 255, 255));
+        assert_eq!(framebuffer.get_color(2, 2), Color::new(0, 0, 0));
+
+        // Con origen negativo la imagen se corre, sin estirarse
+        let mut framebuffer = black_framebuffer(4, 4);
+        framebuffer.blit(&quad(), Some(Rect::new(-1, -1, 3, 3)), 0, 0, Transparency::Opaque);
+        assert_eq!(framebuffer.get_color(0, 0), Color::new(0, 0, 0));
+        assert_eq!(framebuffer.get_color(1, 1), RED);
+        assert_eq!(framebuffer.get_color(2, 2), Color::new(255, 255, 255));
+        assert_eq!(framebuffer.get_color(3, 3), Color::new(0, 0, 0));
+    }
+
+    #[test]
+    fn destination_is_clipped_to_the_buffer() {
+        let mut framebuffer = black_framebuffer(2, 2);
+        framebuffer.blit(&quad(), None, -1, -1, Transparency::Opaque);
+        assert_eq!(framebuffer.get_color(0, 0), Color::new(255, 255, 255));
+        assert_eq!(framebuffer.get_color(1, 1), Color::new(0, 0, 0));
+
+        framebuffer.blit(&quad(), None, 1, 1, Transparency::Opaque);
+        assert_eq!(framebuffer.get_color(1, 1), RED);
+        // Completamente fuera: no hace nada ni entra en pánico
+        framebuffer.blit_scaled(&quad(), None, Rect::new(5, 5, 10, 10), Filter::Bilinear, Transparency::Opaque);
+        framebuffer.blit_scaled(&quad(), None, Rect::new(-20, 0, 10, 10), Filter::Nearest, Transparency::Opaque);
+
+        // Un destino enorme que empieza muy a la izquierda y arriba: la esquina visible es la blanca
+        let far = Rect::new(i32::MIN, i32::MIN, u32::MAX, u32::MAX);
+        let mut framebuffer = black_framebuffer(2, 2);
+        framebuffer.blit_scaled(&quad(), None, far, Filter::Nearest, Transparency::Opaque);
+        assert_eq!(framebuffer.get_color(1, 1), Color::new(255, 255, 255));
+        framebuffer.blit_scaled(&quad(), Some(Rect::new(-1, -1, 3, 3)), far, Filter::Bilinear, Transparency::Opaque);
+    }
+
+    #[test]
+    fn color_key_and_alpha_transparency() {
+        let mut framebuffer = black_framebuffer(2, 2);
+        framebuffer.blit(&quad(), None, 0, 0, Transparency::ColorKey(RED));
+        assert_eq!(framebuffer.get_color(0, 0), Color::new(0, 0, 0));
+        assert_eq!(framebuffer.get_color(0, 1), BLUE);
+
+        let half = RgbaImage::from_pixel(1, 1, Rgba([255, 255, 255, 128]));
+        let mut framebuffer = black_framebuffer(1, 1);
+        framebuffer.blit(&half, None, 0, 0, Transparency::Alpha);
+        let blended = framebuffer.get_color(0, 0);
+        assert!((127..=129).contains(&blended.r), "{:?}", blended);
+    }
+
+    #[test]
+    fn scaling_with_nearest_and_bilinear_sampling() {
+        let row = RgbaImage::from_fn(2, 1, |x, _| {
+            if x == 0 { Rgba([0, 0, 0, 255]) } else { Rgba([200, 200, 200, 255]) }
+        });
+
+        let mut nearest = black_framebuffer(4, 1);
+        nearest.blit_scaled(&row, None, Rect::new(0, 0, 4, 1), Filter::Nearest, Transparency::Opaque);
+        let values: Vec<u8> = (0..4).map(|x| nearest.get_color(x, 0).r).collect();
+        assert_eq!(values, [0, 0, 200, 200]);
+
+        // Los extremos quedan en el color del borde y el centro se interpola
+        let mut bilinear = black_framebuffer(4, 1);
+        bilinear.blit_scaled(&row, None, Rect::new(0, 0, 4, 1), Filter::Bilinear, Transparency::Opaque);
+        let values: Vec<u8> = (0..4).map(|x| bilinear.get_color(x, 0).r).collect();
+        assert_eq!(values, [0, 50, 150, 200]);
+    }
+}
@@ -1,107 +1,617 @@
 use std::fs::File;
-use std::io::{Write, BufWriter};
+use std::io::{Read, Write, BufWriter};
+use std::path::Path;
 
-const BMP_HEADER_SIZE: usize = 54;
-const BMP_PIXEL_OFFSET: usize = 54;
-const BMP_BITS_PER_PIXEL: usize = 24; // Ajustado a 24 bits para RGB
+const FILE_HEADER_SIZE: usize = 14;
+const INFO_HEADER_SIZE: usize = 40; // BITMAPINFOHEADER
+const V4_HEADER_SIZE: usize = 108; // BITMAPV4HEADER, necesario para declarar el canal alfa
 
-pub fn write_bmp_file(
-    file_path: &str,
+const BI_RGB: u32 = 0;
+const BI_RLE8: u32 = 1;
+const BI_BITFIELDS: u32 = 3;
+const BI_ALPHABITFIELDS: u32 = 6;
+
+const LCS_SRGB: u32 = 0x7352_4742; // 'sRGB'
+
+// En RLE8 unos pocos bytes pueden describir una imagen enorme (fin de mapa o saltos), así que
+// su tamaño se limita: 8192 x 8192 píxeles
+const MAX_RLE_PIXELS: usize = 1 << 26;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BmpFormat {
+    // 24 bits BGR; se ignora el byte alto de cada píxel
+    Rgb24,
+    // 32 bits BGRA con cabecera V4, opaco: se ignora el byte alto, como en los píxeles 0x00RRGGBB
+    // del FrameBuffer, y el alfa se escribe siempre como 0xFF
+    Bgra32,
+    // 32 bits BGRA con cabecera V4; el byte alto de cada píxel es el alfa (0xAARRGGBB)
+    Bgra32Alpha,
+}
+
+impl BmpFormat {
+    fn bytes_per_pixel(self) -> usize {
+        match self {
+            BmpFormat::Rgb24 => 3,
+            BmpFormat::Bgra32 | BmpFormat::Bgra32Alpha => 4,
+        }
+    }
+
+    fn header_size(self) -> usize {
+        match self {
+            BmpFormat::Rgb24 => INFO_HEADER_SIZE,
+            BmpFormat::Bgra32 | BmpFormat::Bgra32Alpha => V4_HEADER_SIZE,
+        }
+    }
+}
+
+pub fn write_bmp_file<P: AsRef<Path>>(
+    file_path: P,
     buffer: &[u32],
     width: usize,
     height: usize,
+) -> std::io::Result<()> {
+    write_bmp_file_with_format(file_path, buffer, width, height, BmpFormat::Rgb24)
+}
+
+pub fn write_bmp_file_with_format<P: AsRef<Path>>(
+    file_path: P,
+    buffer: &[u32],
+    width: usize,
+    height: usize,
+    format: BmpFormat,
 ) -> std::io::Result<()> {
     let file = File::create(file_path)?;
     let mut writer = BufWriter::new(file);
 
-    write_bmp_header(&mut writer, width, height)?;
-    write_pixel_data(&mut writer, buffer, width, height)?;
+    write_bmp(&mut writer, buffer, width, height, format)?;
+    writer.flush()
+}
+
+// Codifica a cualquier destino (archivo, Vec<u8> en memoria, socket...)
+pub fn write_bmp<W: Write>(
+    writer: &mut W,
+    buffer: &[u32],
+    width: usize,
+    height: usize,
+    format: BmpFormat,
+) -> std::io::Result<()> {
+    let pixel_count = width
+        .checked_mul(height)
+        .ok_or_else(|| invalid_input("BMP dimensions overflow"))?;
+    if buffer.len() < pixel_count {
+        return Err(invalid_input("pixel buffer is smaller than width * height"));
+    }
+
+    write_bmp_header(writer, width, height, format)?;
+    write_pixel_data(writer, buffer, width, height, format)?;
 
     Ok(())
 }
 
-fn write_bmp_header(
-    file: &mut BufWriter<File>,
+fn row_size(width: usize, format: BmpFormat) -> Option<usize> {
+    Some(width.checked_mul(format.bytes_per_pixel())?.checked_add(3)? / 4 * 4)
+}
+
+fn write_bmp_header<W: Write>(
+    file: &mut W,
     width: usize,
     height: usize,
+    format: BmpFormat,
 ) -> std::io::Result<()> {
-    let file_size = BMP_HEADER_SIZE + (width * height * 3); // 3 bytes por píxel para 24 bits
-    let pixel_data_size = width * height * 3;
+    let header_size = format.header_size();
+    let pixel_offset = FILE_HEADER_SIZE + header_size;
+
+    // Los campos de la cabecera son de 32 bits (ancho y alto con signo)
+    let too_large = || invalid_input("image is too large for the BMP header fields");
+    let pixel_data_size = row_size(width, format)
+        .and_then(|row| row.checked_mul(height))
+        .ok_or_else(too_large)?;
+    let file_size = u32::try_from(pixel_offset + pixel_data_size).map_err(|_| too_large())?;
+    let pixel_data_size = pixel_data_size as u32;
+    let width = i32::try_from(width).map_err(|_| too_large())?;
+    let height = i32::try_from(height).map_err(|_| too_large())?;
 
     // BMP Signature
     file.write_all(b"BM")?;
 
     // File size
-    file.write_all(&(file_size as u32).to_le_bytes())?;
+    file.write_all(&file_size.to_le_bytes())?;
 
     // Reserved
     file.write_all(&[0; 4])?;
 
     // Pixel data offset
-    file.write_all(&(BMP_PIXEL_OFFSET as u32).to_le_bytes())?;
+    file.write_all(&(pixel_offset as u32).to_le_bytes())?;
 
     // Header size
-    file.write_all(&(40 as u32).to_le_bytes())?;
+    file.write_all(&(header_size as u32).to_le_bytes())?;
 
     // Image width
-    file.write_all(&(width as u32).to_le_bytes())?;
+    file.write_all(&width.to_le_bytes())?;
 
     // Image height
-    file.write_all(&(height as u32).to_le_bytes())?;
+    file.write_all(&height.to_le_bytes())?;
 
     // Color planes
-    file.write_all(&(1 as u16).to_le_bytes())?;
+    file.write_all(&1u16.to_le_bytes())?;
 
     // Bits per pixel
-    file.write_all(&(BMP_BITS_PER_PIXEL as u16).to_le_bytes())?;
+    file.write_all(&((format.bytes_per_pixel() * 8) as u16).to_le_bytes())?;
 
-    // Compression method (no compression)
-    file.write_all(&(0 as u32).to_le_bytes())?;
+    // Compression method (no compression, or explicit channel masks for BGRA)
+    let compression = match format {
+        BmpFormat::Rgb24 => BI_RGB,
+        BmpFormat::Bgra32 | BmpFormat::Bgra32Alpha => BI_BITFIELDS,
+    };
+    file.write_all(&compression.to_le_bytes())?;
 
-    // Image size (can be 0 for uncompressed images)
-    file.write_all(&(pixel_data_size as u32).to_le_bytes())?;
+    // Image size
+    file.write_all(&pixel_data_size.to_le_bytes())?;
 
     // Horizontal resolution (pixels per meter)
-    file.write_all(&(2835 as u32).to_le_bytes())?; // 72 DPI
+    file.write_all(&2835u32.to_le_bytes())?; // 72 DPI
 
     // Vertical resolution (pixels per meter)
-    file.write_all(&(2835 as u32).to_le_bytes())?; // 72 DPI
+    file.write_all(&2835u32.to_le_bytes())?; // 72 DPI
 
     // Number of colors (0 means default)
-    file.write_all(&(0 as u32).to_le_bytes())?;
+    file.write_all(&0u32.to_le_bytes())?;
 
     // Important colors (0 means all are important)
-    file.write_all(&(0 as u32).to_le_bytes())?;
+    file.write_all(&0u32.to_le_bytes())?;
+
+    if format != BmpFormat::Rgb24 {
+        // Red, green, blue and alpha masks
+        for mask in [0x00FF_0000u32, 0x0000_FF00, 0x0000_00FF, 0xFF00_0000] {
+            file.write_all(&mask.to_le_bytes())?;
+        }
+
+        // Color space
+        file.write_all(&LCS_SRGB.to_le_bytes())?;
+
+        // Endpoints and gamma (unused for sRGB)
+        file.write_all(&[0; 48])?;
+    }
 
     Ok(())
 }
 
-fn write_pixel_data(
-    file: &mut BufWriter<File>,
+fn write_pixel_data<W: Write>(
+    file: &mut W,
     buffer: &[u32],
     width: usize,
     height: usize,
+    format: BmpFormat,
 ) -> std::io::Result<()> {
-    let row_size = (BMP_BITS_PER_PIXEL / 8) * width;
-    let padding_size = (4 - (row_size % 4)) % 4;
+    let bytes_per_pixel = format.bytes_per_pixel();
+    let opaque = if format == BmpFormat::Bgra32 { 0xFF00_0000 } else { 0 };
+    let padding_size = (4 - (bytes_per_pixel * width % 4)) % 4;
+    let mut row = Vec::with_capacity(bytes_per_pixel * width + padding_size);
 
     for y in (0..height).rev() {
         let row_start = y * width;
         let row_end = row_start + width;
 
+        row.clear();
         for &pixel in &buffer[row_start..row_end] {
-            let b = (pixel & 0xFF) as u8;
-            let g = ((pixel >> 8) & 0xFF) as u8;
-            let r = ((pixel >> 16) & 0xFF) as u8;
+            let bgra = (pixel | opaque).to_le_bytes();
+            row.extend_from_slice(&bgra[..bytes_per_pixel]);
+        }
+
+        // Padding bytes
+        row.resize(row.len() + padding_size, 0);
+        file.write_all(&row)?;
+    }
+
+    Ok(())
+}
+
+// Imagen decodificada; cada píxel es 0xAARRGGBB (alfa 0xFF en formatos sin canal alfa)
+#[derive(Debug)]
+pub struct BmpImage {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u32>,
+}
+
+pub fn read_bmp_file<P: AsRef<Path>>(file_path: P) -> std::io::Result<BmpImage> {
+    let mut data = Vec::new();
+    File::open(file_path)?.read_to_end(&mut data)?;
+    read_bmp(&data)
+}
+
+// Decodifica BMP sin comprimir de 24 y 32 bits, de 8 bits con paleta y RLE8, en ambos sentidos de filas
+pub fn read_bmp(data: &[u8]) -> std::io::Result<BmpImage> {
+    if data.len() < 14 + 40 || &data[0..2] != b"BM" {
+        return Err(invalid_data("not a BMP file"));
+    }
+    let pixel_offset = read_u32(data, 10)? as usize;
+
+    let dib = &data[14..];
+    let header_size = read_u32(dib, 0)? as usize;
+    if header_size < 40 {
+        return Err(invalid_data("unsupported BMP header (OS/2 bitmaps are not supported)"));
+    }
+    let raw_width = read_i32(dib, 4)?;
+    let raw_height = read_i32(dib, 8)?;
+    let bits_per_pixel = read_u16(dib, 14)?;
+    let compression = read_u32(dib, 16)?;
+    let colors_used = read_u32(dib, 32)? as usize;
+
+    if raw_width <= 0 || raw_height == 0 || raw_height == i32::MIN {
+        return Err(invalid_data("invalid BMP dimensions"));
+    }
+    let width = raw_width as usize;
+    let height = raw_height.unsigned_abs() as usize;
+    // Altura negativa: las filas se guardan de arriba hacia abajo
+    let top_down = raw_height < 0;
+    let pixel_count = width
+        .checked_mul(height)
+        .ok_or_else(|| invalid_data("BMP dimensions overflow"))?;
+
+    let pixel_data = data
+        .get(pixel_offset..)
+        .ok_or_else(|| invalid_data("pixel data offset is past the end of the file"))?;
+
+    // Las dimensiones de la cabecera no se creen hasta ver que el archivo las respalda (o, en RLE8,
+    // que no pasan del límite); los formatos sin soporte se rechazan antes de reservar nada
+    let unsupported = || {
+        invalid_data(&format!(
+            "unsupported BMP format: {} bits per pixel, compression {}",
+            bits_per_pixel, compression
+        ))
+    };
+    match stored_row_size(bits_per_pixel, compression, width) {
+        Some(row_size) => {
+            let needed = row_size
+                .checked_mul(height)
+                .ok_or_else(|| invalid_data("BMP dimensions overflow"))?;
+            if pixel_data.len() < needed {
+                return Err(invalid_data("BMP pixel data is truncated"));
+            }
+        }
+        None if (bits_per_pixel, compression) == (8, BI_RLE8) => {
+            if pixel_count > MAX_RLE_PIXELS {
+                return Err(invalid_data("RLE8 bitmap is too large"));
+            }
+        }
+        None => return Err(unsupported()),
+    }
+    let mut pixels = vec![0u32; pixel_count];
+
+    match (bits_per_pixel, compression) {
+        (24, BI_RGB) | (32, BI_RGB) => {
+            let bytes_per_pixel = bits_per_pixel as usize / 8;
+            let row_size = (bytes_per_pixel * width).div_ceil(4) * 4;
+            for_each_row(pixel_data, row_size, height, top_down, |y, row| {
+                for x in 0..width {
+                    let p = &row[x * bytes_per_pixel..];
+                    pixels[y * width + x] = 0xFF00_0000 | (p[2] as u32) << 16 | (p[1] as u32) << 8 | p[0] as u32;
+                }
+            })?;
+        }
+        (32, BI_BITFIELDS) | (32, BI_ALPHABITFIELDS) => {
+            // Las máscaras van dentro de la cabecera V4/V5 o justo después de BITMAPINFOHEADER
+            let has_alpha = header_size >= 56 || compression == BI_ALPHABITFIELDS;
+            let masks_end = if has_alpha { 56 } else { 52 };
+            let masks = dib
+                .get(40..masks_end)
+                .ok_or_else(|| invalid_data("BMP colour masks are truncated"))?;
+            let mask = |i: usize| read_u32(masks, i * 4).unwrap_or(0);
+            let (red, green, blue) = (mask(0), mask(1), mask(2));
+            let alpha = if has_alpha { mask(3) } else { 0 };
+
+            let row_size = 4 * width;
+            for_each_row(pixel_data, row_size, height, top_down, |y, row| {
+                for x in 0..width {
+                    let value = u32::from_le_bytes([row[x * 4], row[x * 4 + 1], row[x * 4 + 2], row[x * 4 + 3]]);
+                    let a = if alpha == 0 { 0xFF } else { extract_channel(value, alpha) };
+                    pixels[y * width + x] = (a as u32) << 24
+                        | (extract_channel(value, red) as u32) << 16
+                        | (extract_channel(value, green) as u32) << 8
+                        | extract_channel(value, blue) as u32;
+                }
+            })?;
+        }
+        (8, BI_RGB) | (8, BI_RLE8) => {
+            let palette = read_palette(data, 14 + header_size, colors_used)?;
+            let color = |index: u8| palette.get(index as usize).copied().unwrap_or(0xFF00_0000);
 
-            file.write_all(&[b, g, r])?;
+            if compression == BI_RGB {
+                let row_size = width.div_ceil(4) * 4;
+                for_each_row(pixel_data, row_size, height, top_down, |y, row| {
+                    for x in 0..width {
+                        pixels[y * width + x] = color(row[x]);
+                    }
+                })?;
+            } else {
+                if top_down {
+                    return Err(invalid_data("RLE8 bitmaps must be stored bottom-up"));
+                }
+                pixels.fill(color(0));
+                decode_rle8(pixel_data, width, height, |x, y, index| {
+                    pixels[(height - 1 - y) * width + x] = color(index);
+                })?;
+            }
         }
+        _ => return Err(unsupported()),
+    }
+
+    Ok(BmpImage { width, height, pixels })
+}
 
-        // Write padding bytes
-        if padding_size > 0 {
-            file.write_all(&vec![0; padding_size])?;
+// Bytes por fila almacenada de los formatos sin comprimir; None para RLE8 y formatos desconocidos
+fn stored_row_size(bits_per_pixel: u16, compression: u32, width: usize) -> Option<usize> {
+    let bytes_per_pixel = match (bits_per_pixel, compression) {
+        (24, BI_RGB) => 3,
+        (32, BI_RGB) | (32, BI_BITFIELDS) | (32, BI_ALPHABITFIELDS) => 4,
+        (8, BI_RGB) => 1,
+        _ => return None,
+    };
+    Some(width.checked_mul(bytes_per_pixel)?.checked_add(3)? / 4 * 4)
+}
+
+// Recorre las filas almacenadas y entrega cada una con su índice de arriba hacia abajo
+fn for_each_row(
+    pixel_data: &[u8],
+    row_size: usize,
+    height: usize,
+    top_down: bool,
+    mut f: impl FnMut(usize, &[u8]),
+) -> std::io::Result<()> {
+    let needed = row_size
+        .checked_mul(height)
+        .ok_or_else(|| invalid_data("BMP dimensions overflow"))?;
+    if pixel_data.len() < needed {
+        return Err(invalid_data("BMP pixel data is truncated"));
+    }
+    for (stored, row) in pixel_data.chunks_exact(row_size).take(height).enumerate() {
+        let y = if top_down { stored } else { height - 1 - stored };
+        f(y, row);
+    }
+    Ok(())
+}
+
+// Decodifica RLE8; `put` recibe coordenadas con y contada desde la fila inferior
+fn decode_rle8(
+    data: &[u8],
+    width: usize,
+    height: usize,
+    mut put: impl FnMut(usize, usize, u8),
+) -> std::io::Result<()> {
+    let truncated = || invalid_data("RLE8 data is truncated");
+    let (mut x, mut y) = (0usize, 0usize);
+    let mut i = 0;
+
+    while y < height {
+        let count = *data.get(i).ok_or_else(truncated)?;
+        let value = *data.get(i + 1).ok_or_else(truncated)?;
+        i += 2;
+
+        if count > 0 {
+            // Tramo codificado: `count` píxeles del mismo índice
+            for _ in 0..count {
+                if x < width {
+                    put(x, y, value);
+                }
+                x += 1;
+            }
+            continue;
+        }
+
+        match value {
+            0 => {
+                x = 0;
+                y += 1;
+            }
+            1 => break,
+            2 => {
+                let dx = *data.get(i).ok_or_else(truncated)?;
+                let dy = *data.get(i + 1).ok_or_else(truncated)?;
+                i += 2;
+                x += dx as usize;
+                y += dy as usize;
+            }
+            n => {
+                // Tramo absoluto: `n` índices literales, rellenados a múltiplo de 2 bytes
+                let n = n as usize;
+                let run = data.get(i..i + n).ok_or_else(truncated)?;
+                for &index in run {
+                    if x < width && y < height {
+                        put(x, y, index);
+                    }
+                    x += 1;
+                }
+                i += n + (n % 2);
+            }
         }
     }
 
     Ok(())
 }
+
+fn read_palette(data: &[u8], offset: usize, colors_used: usize) -> std::io::Result<Vec<u32>> {
+    let count = if colors_used == 0 { 256 } else { colors_used.min(256) };
+    let bytes = data
+        .get(offset..offset + count * 4)
+        .ok_or_else(|| invalid_data("BMP palette is truncated"))?;
+    Ok(bytes
+        .chunks_exact(4)
+        .map(|entry| 0xFF00_0000 | (entry[2] as u32) << 16 | (entry[1] as u32) << 8 | entry[0] as u32)
+        .collect())
+}
+
+// Extrae un canal con la máscara dada y lo normaliza a 8 bits
+fn extract_channel(value: u32, mask: u32) -> u8 {
+    if mask == 0 {
+        return 0;
+    }
+    let shifted = (value & mask) >> mask.trailing_zeros();
+    let max = mask >> mask.trailing_zeros();
+    ((shifted as u64 * 255 + max as u64 / 2) / max as u64) as u8
+}
+
+fn read_u16(data: &[u8], offset: usize) -> std::io::Result<u16> {
+    data.get(offset..offset + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .ok_or_else(|| invalid_data("BMP header is truncated"))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> std::io::Result<u32> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| invalid_data("BMP header is truncated"))
+}
+
+fn read_i32(data: &[u8], offset: usize) -> std::io::Result<i32> {
+    read_u32(data, offset).map(|v| v as i32)
+}
+
+fn invalid_data(message: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message)
+}
+
+fn invalid_input(message: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidInput, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Archivo BMP con BITMAPINFOHEADER, la paleta dada y los datos de píxeles tal cual
+    fn bmp_bytes(
+        width: i32,
+        height: i32,
+        bits_per_pixel: u16,
+        compression: u32,
+        palette: &[u32],
+        data: &[u8],
+    ) -> Vec<u8> {
+        let pixel_offset = (FILE_HEADER_SIZE + INFO_HEADER_SIZE + palette.len() * 4) as u32;
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"BM");
+        bytes.extend_from_slice(&(pixel_offset + data.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&[0; 4]);
+        bytes.extend_from_slice(&pixel_offset.to_le_bytes());
+        bytes.extend_from_slice(&(INFO_HEADER_SIZE as u32).to_le_bytes());
+        bytes.extend_from_slice(&width.to_le_bytes());
+        bytes.extend_from_slice(&height.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&bits_per_pixel.to_le_bytes());
+        bytes.extend_from_slice(&compression.to_le_bytes());
+        bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&[0; 8]);
+        bytes.extend_from_slice(&(palette.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&[0; 4]);
+        for entry in palette {
+            bytes.extend_from_slice(&entry.to_le_bytes());
+        }
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    #[test]
+    fn reads_24_bit_rows_with_padding() {
+        // 2x2 de abajo hacia arriba: cada fila de 6 bytes se rellena a 8
+        let data = [
+            0, 0, 255, 0, 255, 0, 0, 0, // fila inferior: rojo, verde
+            255, 0, 0, 255, 255, 255, 0, 0, // fila superior: azul, blanco
+        ];
+        let image = read_bmp(&bmp_bytes(2, 2, 24, BI_RGB, &[], &data)).unwrap();
+        assert_eq!((image.width, image.height), (2, 2));
+        assert_eq!(image.pixels, [0xFF00_00FF, 0xFFFF_FFFF, 0xFFFF_0000, 0xFF00_FF00]);
+    }
+
+    #[test]
+    fn reads_32_bit_top_down_rows() {
+        let data = [
+            0x10, 0x20, 0x30, 0, // fila superior
+            0x40, 0x50, 0x60, 0, // fila inferior
+        ];
+        let image = read_bmp(&bmp_bytes(1, -2, 32, BI_RGB, &[], &data)).unwrap();
+        assert_eq!(image.pixels, [0xFF30_2010, 0xFF60_5040]);
+    }
+
+    #[test]
+    fn reads_8_bit_paletted_pixels() {
+        let palette = [0x0000_0000, 0x00FF_8000];
+        let data = [1, 0, 0, 0, 0, 1, 0, 0];
+        let image = read_bmp(&bmp_bytes(2, 2, 8, BI_RGB, &palette, &data)).unwrap();
+        assert_eq!(image.pixels, [0xFF00_0000, 0xFFFF_8000, 0xFFFF_8000, 0xFF00_0000]);
+    }
+
+    #[test]
+    fn reads_rle8_runs_literals_and_deltas() {
+        let palette = [0x0000_0000, 0x00FF_0000, 0x0000_FF00];
+        let data = [
+            3, 1, 0, 0, // fila inferior: tres píxeles de índice 1
+            0, 3, 2, 1, 2, 0, 0, 0, // fila 1: literales 2 1 2 con relleno
+            0, 2, 1, 0, // salto al píxel 1 de la fila 2
+            1, 2, 0, 1, // un píxel de índice 2 y fin del mapa
+        ];
+        let image = read_bmp(&bmp_bytes(3, 3, 8, BI_RLE8, &palette, &data)).unwrap();
+        let (black, red, green) = (0xFF00_0000, 0xFFFF_0000, 0xFF00_FF00);
+        assert_eq!(image.pixels, [black, green, black, green, red, green, red, red, red]);
+    }
+
+    #[test]
+    fn rejects_dimensions_the_file_cannot_hold() {
+        // Sin datos de píxeles detrás de una cabecera de 2^30 x 2^30: error antes de reservar memoria
+        let huge = bmp_bytes(1 << 30, 1 << 30, 24, BI_RGB, &[], &[]);
+        assert_eq!(read_bmp(&huge).unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+        let huge_rle = bmp_bytes(1 << 30, 1 << 30, 8, BI_RLE8, &[0], &[0, 1]);
+        assert_eq!(read_bmp(&huge_rle).unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+        // Un formato sin soporte con dimensiones enormes se rechaza sin reservar memoria
+        let huge_16 = bmp_bytes(1 << 30, 1 << 30, 16, BI_RGB, &[], &[0; 4]);
+        assert!(read_bmp(&huge_16).unwrap_err().to_string().contains("16 bits per pixel"));
+        let huge_bitfields = bmp_bytes(1 << 30, 1 << 30, 16, BI_BITFIELDS, &[], &[0; 16]);
+        assert!(read_bmp(&huge_bitfields).unwrap_err().to_string().contains("unsupported BMP format"));
+        let truncated = bmp_bytes(2, 2, 24, BI_RGB, &[], &[0; 10]);
+        assert!(read_bmp(&truncated).is_err());
+    }
+
+    #[test]
+    fn encodes_in_memory_with_padded_rows() {
+        let mut bytes = Vec::new();
+        write_bmp(&mut bytes, &[0x0011_2233, 0x0044_5566, 0, 0], 2, 2, BmpFormat::Rgb24).unwrap();
+        // Cabeceras de 54 bytes y dos filas de 6 bytes rellenadas a 8
+        assert_eq!(bytes.len(), 54 + 16);
+        assert_eq!(&bytes[0..2], b"BM");
+        assert_eq!(read_u32(&bytes, 2).unwrap(), 70);
+        // La fila superior va al final, en orden BGR
+        assert_eq!(&bytes[62..68], [0x33, 0x22, 0x11, 0x66, 0x55, 0x44]);
+    }
+
+    #[test]
+    fn rejects_sizes_that_do_not_fit_the_header() {
+        let mut bytes = Vec::new();
+        let error = write_bmp(&mut bytes, &[], usize::MAX, 2, BmpFormat::Rgb24).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+        // Sin píxeles que copiar, pero el ancho no cabe en el campo de 32 bits con signo
+        let error = write_bmp(&mut bytes, &[], 1 << 31, 0, BmpFormat::Bgra32).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+        let error = write_bmp(&mut bytes, &[0; 3], 2, 2, BmpFormat::Rgb24).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+        assert!(bytes.is_empty());
+    }
+
+    #[test]
+    fn written_images_read_back_unchanged() {
+        let pixels = [0x00FF_0000, 0x0000_FF00, 0x0000_00FF, 0x0012_3456, 0x00FF_FFFF, 0];
+        for format in [BmpFormat::Rgb24, BmpFormat::Bgra32] {
+            let mut bytes = Vec::new();
+            write_bmp(&mut bytes, &pixels, 3, 2, format).unwrap();
+            let image = read_bmp(&bytes).unwrap();
+            assert_eq!((image.width, image.height), (3, 2));
+            // Los píxeles del FrameBuffer no tienen alfa: se leen opacos
+            let opaque: Vec<u32> = pixels.iter().map(|pixel| pixel | 0xFF00_0000).collect();
+            assert_eq!(image.pixels, opaque, "{:?}", format);
+        }
+
+        let translucent = [0x80FF_0000, 0x0000_FF00, 0xFF00_00FF, 0x4012_3456];
+        let mut bytes = Vec::new();
+        write_bmp(&mut bytes, &translucent, 2, 2, BmpFormat::Bgra32Alpha).unwrap();
+        assert_eq!(read_bmp(&bytes).unwrap().pixels, translucent);
+    }
+}
@@ -0,0 +1,78 @@
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::framebuffer::FrameBuffer;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ImageFormat {
+    Bmp,
+    Png,
+}
+
+impl ImageFormat {
+    pub fn from_path(path: &Path) -> Option<ImageFormat> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "bmp" => Some(ImageFormat::Bmp),
+            "png" => Some(ImageFormat::Png),
+            _ => None,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ImageFormat::Bmp => "bmp",
+            ImageFormat::Png => "png",
+        }
+    }
+}
+
+// Guarda el cuadro en el formato que indique la extensión del archivo
+pub fn save_frame(framebuffer: &FrameBuffer, path: &Path) -> Result<(), Box<dyn Error>> {
+    match ImageFormat::from_path(path) {
+        Some(ImageFormat::Bmp) => framebuffer.write_to_bmp(path)?,
+        Some(ImageFormat::Png) => framebuffer.write_to_png(path)?,
+        None => return Err(format!("unsupported image extension: {}", path.display()).into()),
+    }
+    Ok(())
+}
+
+// Guarda una captura con nombre de fecha y hora (UTC) dentro de `dir`, creándolo si no existe
+pub fn save_screenshot(framebuffer: &FrameBuffer, dir: &Path, format: ImageFormat) -> Result<PathBuf, Box<dyn Error>> {
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(format!("screenshot_{}.{}", timestamp(), format.extension()));
+    save_frame(framebuffer, &path)?;
+    Ok(path)
+}
+
+// Fecha y hora actual en UTC como AAAA-MM-DD_HH-MM-SS_mmm
+pub fn timestamp() -> String {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    let seconds = now.as_secs();
+    let (year, month, day) = civil_from_days((seconds / 86_400) as i64);
+    let time = seconds % 86_400;
+    format!(
+        "{:04}-{:02}-{:02}_{:02}-{:02}-{:02}_{:03}",
+        year,
+        month,
+        day,
+        time / 3600,
+        time / 60 % 60,
+        time % 60,
+        now.subsec_millis()
+    )
+}
+
+// Convierte días desde 1970-01-01 a fecha del calendario gregoriano (algoritmo de Howard Hinnant)
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
@@ -1,20 +1,19 @@
-use crate::framebuffer::FrameBuffer;
+use crate::maze::is_open;
 use crate::player::Player;
-use crate::color::Color;
 
 pub struct Intersect {
     pub distance: f32,
     pub impact: char
 }
 
-pub fn cast_ray(framebuffer: &mut FrameBuffer, maze: &Vec<Vec<char>>, player: &Player,
-    a: f32, block_size: usize, draw_line: bool) -> Intersect {
+pub fn cast_ray(maze: &[Vec<char>], player: &Player, a: f32, block_size: usize) -> Intersect {
     let mut d = 0.0;
     let mut x;
     let mut y;
 
-    // Establecer el color actual
-    framebuffer.set_current_color(Color::new(255, 0, 0));
+    // El rayo se detiene al salir del laberinto, sin depender de la resolución de pantalla
+    let maze_width = maze.first().map_or(0, |row| row.len()) * block_size;
+    let maze_height = maze.len() * block_size;
 
     loop {
         let cos = a.cos();
@@ -22,8 +21,8 @@ pub fn cast_ray(framebuffer: &mut FrameBuffer, maze: &Vec<Vec<char>>, player: &P
         x = (player.pos.x + d * cos) as usize;
         y = (player.pos.y + d * sin) as usize;
 
-        // Verificar que x y y están dentro de los límites del framebuffer
-        if x >= framebuffer.width || y >= framebuffer.height {
+        // Verificar que x y y están dentro de los límites del laberinto
+        if x >= maze_width || y >= maze_height {
             break;
         }
 
@@ -36,12 +35,7 @@ pub fn cast_ray(framebuffer: &mut FrameBuffer, maze: &Vec<Vec<char>>, player: &P
             continue;
         }
 
-        if draw_line {
-            // Usar draw_rectangle para dibujar un píxel de 1x1
-            framebuffer.draw_rectangle(x, y, 1, 1);  
-        }
-
-        if maze[j][i] != ' ' {
+        if !is_open(maze[j][i]) {
             return Intersect {
                 distance: d,
                 impact: maze[j][i]
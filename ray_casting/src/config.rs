@@ -0,0 +1,510 @@
+use std::error::Error;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer};
+use crate::blit::Filter;
+use crate::recorder::RecordingFormat;
+
+// Archivo que se busca si no se indica `--config`: primero en el directorio actual, después junto
+// al ejecutable, en la configuración del usuario y, al correr con `cargo run`, en el crate
+pub const CONFIG_FILE_NAME: &str = "ray_casting.toml";
+// Nombre dentro de `$XDG_CONFIG_HOME/ray_casting/` (o `~/.config/ray_casting/`)
+pub const USER_CONFIG_FILE_NAME: &str = "config.toml";
+
+pub const USAGE: &str = "\
+Usage: ray_casting [OPTIONS]
+
+Options:
+  --config <FILE>          Configuration file (default: ray_casting.toml in the current directory
+                           or next to the executable, then ~/.config/ray_casting/config.toml)
+  --size <WxH>             Window resolution, e.g. 1280x720
+  --render-scale <SCALE>   Internal render scale, between 0 and 1
+  --filter <FILTER>        Upscaling filter: nearest or bilinear
+  --threads <N>            Threads for the 3D view (0 = one per CPU)
+  --backend <NAME>         Display: window, terminal (truecolour) or ascii
+  --fov <DEGREES>          Horizontal field of view
+  --sensitivity <VALUE>    Mouse sensitivity, in radians per pixel
+  --pitch-sensitivity <VALUE>
+                           Vertical mouse sensitivity, in view heights per pixel
+  --invert-mouse-y         Moving the mouse up looks down
+  --mouse-smoothing <0..1> Fraction of the previous mouse movement kept each frame
+  --absolute-mouse         Leave the cursor visible and free instead of capturing it
+  --map <FILE>             Maze file, searched in the asset roots
+  --asset-root <DIR>       Directory containing the map and the assets/ folder
+  --search-path <DIR>      Extra directory to search for assets (repeatable)
+  --music-volume <0..1>    Music volume
+  --sfx-volume <0..1>      Sound effect volume
+  --ui-volume <0..1>       Interface sound volume
+  --no-audio               Run without opening an audio device
+  --debug-clip             Report out-of-bounds framebuffer writes
+  --profiler               Start with the frame-time overlay visible (toggle with F3)
+  --profile-csv <FILE>     Write frame-time statistics to a CSV file on exit
+  --record-demo <FILE>     Record per-tick input to a demo file
+  --play-demo <FILE>       Play back a recorded demo
+  --help                   Print this help
+
+Other modes:
+  --render-frame <OUT> [--map --texture --pos x,y --angle RADIANS --fov DEGREES --size WxH --mode 2d|3d]
+  --replay <DEMO> [--map <FILE>]
+
+Every option except --config, --record-demo and --play-demo can also be set in the
+configuration file. Command line options take precedence. --render-frame and --replay
+read the map and assets from the configured asset roots; the replayed map defaults to
+the one recorded in the demo, then to the configured map.
+";
+
+// Configuración del juego. Las claves que falten en el archivo toman los valores por defecto.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub display: DisplayConfig,
+    pub player: PlayerConfig,
+    pub paths: PathsConfig,
+    pub audio: AudioConfig,
+    pub recording: RecordingConfig,
+    pub debug: DebugConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DisplayConfig {
+    pub width: usize,
+    pub height: usize,
+    // Escala de render interna: 1.0 = resolución de la ventana
+    pub render_scale: f32,
+    #[serde(deserialize_with = "filter")]
+    pub filter: Filter,
+    // Hilos que dibujan la vista 3D; 0 = uno por núcleo
+    pub threads: usize,
+    #[serde(deserialize_with = "backend")]
+    pub backend: Backend,
+}
+
+// Dónde se muestra el juego: ventana o terminal (para jugar por SSH)
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Backend {
+    Window,
+    // Bloques de media altura con color verdadero
+    Terminal,
+    // Caracteres ASCII según la luminancia
+    Ascii,
+}
+
+impl Backend {
+    pub fn parse(value: &str) -> Option<Backend> {
+        match value.to_ascii_lowercase().as_str() {
+            "window" => Some(Backend::Window),
+            "terminal" => Some(Backend::Terminal),
+            "ascii" => Some(Backend::Ascii),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PlayerConfig {
+    // En grados, que es más cómodo de escribir a mano
+    pub fov: f32,
+    pub mouse_sensitivity: f32,
+    // Cabeceo por píxel, en fracciones del alto de la vista
+    pub pitch_sensitivity: f32,
+    // Captura el mouse: cursor oculto y devuelto al centro de la ventana en cada cuadro
+    pub relative_mouse: bool,
+    pub invert_mouse_y: bool,
+    // 0 = sin suavizado; cerca de 1 = muy suave pero con retraso
+    pub mouse_smoothing: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PathsConfig {
+    // Se busca en `asset_root` y luego en `search_paths`
+    pub map: PathBuf,
+    // Relativos al archivo de configuración
+    pub asset_root: PathBuf,
+    pub search_paths: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AudioConfig {
+    pub enabled: bool,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub ui_volume: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RecordingConfig {
+    #[serde(deserialize_with = "recording_format")]
+    pub format: RecordingFormat,
+    pub fps: f32,
+    pub max_frames: Option<usize>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DebugConfig {
+    // Cuenta e informa las escrituras fuera del framebuffer
+    pub clip: bool,
+    // Muestra el gráfico de tiempos por cuadro desde el inicio
+    pub profiler: bool,
+    // Al salir, guarda mínimo, promedio y p99 de cada fase en este CSV
+    pub profile_csv: Option<PathBuf>,
+}
+
+impl Default for DisplayConfig {
+    fn default() -> DisplayConfig {
+        DisplayConfig { width: 1000, height: 900, render_scale: 1.0, filter: Filter::Nearest, threads: 0, backend: Backend::Window }
+    }
+}
+
+impl Default for PlayerConfig {
+    fn default() -> PlayerConfig {
+        PlayerConfig {
+            fov: 60.0,
+            mouse_sensitivity: 0.005,
+            pitch_sensitivity: 0.001,
+            relative_mouse: true,
+            invert_mouse_y: false,
+            mouse_smoothing: 0.3,
+        }
+    }
+}
+
+impl Default for PathsConfig {
+    fn default() -> PathsConfig {
+        PathsConfig { map: PathBuf::from("maze.txt"), asset_root: PathBuf::from("."), search_paths: Vec::new() }
+    }
+}
+
+impl Default for AudioConfig {
+    fn default() -> AudioConfig {
+        AudioConfig { enabled: true, music_volume: 1.0, sfx_volume: 1.0, ui_volume: 1.0 }
+    }
+}
+
+impl Default for RecordingConfig {
+    fn default() -> RecordingConfig {
+        RecordingConfig { format: RecordingFormat::Gif, fps: 15.0, max_frames: None }
+    }
+}
+
+// Resultado de leer la línea de comandos
+pub enum Command {
+    Run(Config),
+    Help,
+}
+
+impl Config {
+    pub fn parse(text: &str) -> Result<Config, Box<dyn Error>> {
+        let config: Config = toml::from_str(text)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    // Lee el archivo y resuelve las rutas de recursos respecto a su directorio, para que el juego
+    // funcione sin importar desde dónde se ejecute
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Config, Box<dyn Error>> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let mut config = Config::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+        if let Some(dir) = path.parent() {
+            config.paths.asset_root = dir.join(&config.paths.asset_root);
+            for path in &mut config.paths.search_paths {
+                *path = dir.join(&*path);
+            }
+        }
+        Ok(config)
+    }
+
+    // Configuración para el modo con ventana: archivo (si existe) más opciones de la línea de comandos
+    pub fn from_args(args: &[String]) -> Result<Command, Box<dyn Error>> {
+        if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+            return Ok(Command::Help);
+        }
+
+        let mut config = Config::from_file_args(args)?;
+
+        let mut rest = args.iter().skip(1);
+        while let Some(flag) = rest.next() {
+            let mut value = || rest.next().map(String::as_str).ok_or(format!("{} needs a value", flag));
+            match flag.as_str() {
+                "--size" => {
+                    let (width, height) = value()?
+                        .split_once('x')
+                        .ok_or("--size expects WIDTHxHEIGHT")?;
+                    config.display.width = parse_number(width)?;
+                    config.display.height = parse_number(height)?;
+                }
+                "--render-scale" => config.display.render_scale = parse_number(value()?)?,
+                "--filter" => {
+                    let name = value()?;
+                    config.display.filter = Filter::parse(name).ok_or(format!("unknown filter '{}'", name))?;
+                }
+                "--threads" => config.display.threads = parse_number(value()?)?,
+                "--backend" => {
+                    let name = value()?;
+                    config.display.backend = Backend::parse(name).ok_or(format!("unknown backend '{}'", name))?;
+                }
+                "--fov" => config.player.fov = parse_number(value()?)?,
+                "--sensitivity" => config.player.mouse_sensitivity = parse_number(value()?)?,
+                "--pitch-sensitivity" => config.player.pitch_sensitivity = parse_number(value()?)?,
+                "--invert-mouse-y" => config.player.invert_mouse_y = true,
+                "--mouse-smoothing" => config.player.mouse_smoothing = parse_number(value()?)?,
+                "--absolute-mouse" => config.player.relative_mouse = false,
+                "--map" => config.paths.map = PathBuf::from(value()?),
+                "--asset-root" => config.paths.asset_root = PathBuf::from(value()?),
+                "--search-path" => config.paths.search_paths.push(PathBuf::from(value()?)),
+                "--music-volume" => config.audio.music_volume = parse_number(value()?)?,
+                "--sfx-volume" => config.audio.sfx_volume = parse_number(value()?)?,
+                "--ui-volume" => config.audio.ui_volume = parse_number(value()?)?,
+                "--no-audio" => config.audio.enabled = false,
+                "--debug-clip" => config.debug.clip = true,
+                "--profiler" => config.debug.profiler = true,
+                "--profile-csv" => config.debug.profile_csv = Some(PathBuf::from(value()?)),
+                // Ya leídas arriba o en `main`
+                "--config" | "--record-demo" | "--play-demo" => {
+                    value()?;
+                }
+                other => return Err(format!("unknown option: {} (see --help)", other).into()),
+            }
+        }
+
+        config.validate()?;
+        Ok(Command::Run(config))
+    }
+
+    // Solo el archivo (el de `--config` o el que se encuentre), sin aplicar las demás opciones.
+    // Lo usan `--render-frame` y `--replay`, que tienen sus propias opciones.
+    pub fn from_file_args(args: &[String]) -> Result<Config, Box<dyn Error>> {
+        let explicit = args.iter().position(|arg| arg == "--config").map(|index| {
+            args.get(index + 1).map(PathBuf::from).ok_or("--config needs a value")
+        });
+        match explicit.transpose()?.or_else(find_config_file) {
+            Some(path) => Config::load(path),
+            None => Ok(Config::default()),
+        }
+    }
+
+    // Directorios donde se buscan los recursos, en orden de prioridad
+    pub fn asset_roots(&self) -> Vec<PathBuf> {
+        std::iter::once(self.paths.asset_root.clone())
+            .chain(self.paths.search_paths.iter().cloned())
+            .collect()
+    }
+
+    // Tamaño del framebuffer interno según la escala de render
+    pub fn render_size(&self) -> (usize, usize) {
+        let scale = |size: usize| ((size as f32 * self.display.render_scale) as usize).max(1);
+        (scale(self.display.width), scale(self.display.height))
+    }
+
+    // Hilos para la vista 3D, resolviendo 0 como uno por núcleo
+    pub fn render_threads(&self) -> usize {
+        match self.display.threads {
+            0 => std::thread::available_parallelism().map_or(1, |n| n.get()),
+            threads => threads,
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let display = &self.display;
+        if display.width == 0 || display.height == 0 {
+            return Err("display size must be at least 1x1".into());
+        }
+        if !(display.render_scale > 0.0 && display.render_scale <= 1.0) {
+            return Err(format!("render_scale must be in (0, 1], got {}", display.render_scale));
+        }
+        if !(self.player.fov > 0.0 && self.player.fov < 180.0) {
+            return Err(format!("fov must be between 0 and 180 degrees, got {}", self.player.fov));
+        }
+        if !(0.0..1.0).contains(&self.player.mouse_smoothing) {
+            return Err(format!("mouse_smoothing must be in [0, 1), got {}", self.player.mouse_smoothing));
+        }
+        for (name, volume) in [
+            ("music_volume", self.audio.music_volume),
+            ("sfx_volume", self.audio.sfx_volume),
+            ("ui_volume", self.audio.ui_volume),
+        ] {
+            if !(0.0..=1.0).contains(&volume) {
+                return Err(format!("{} must be between 0 and 1, got {}", name, volume));
+            }
+        }
+        // Se admite negativa para invertir el ratón
+        if !self.player.mouse_sensitivity.is_finite() {
+            return Err(format!("mouse_sensitivity must be finite, got {}", self.player.mouse_sensitivity));
+        }
+        if !self.player.pitch_sensitivity.is_finite() {
+            return Err(format!("pitch_sensitivity must be finite, got {}", self.player.pitch_sensitivity));
+        }
+        if !(self.recording.fps.is_finite() && self.recording.fps > 0.0) {
+            return Err(format!("recording fps must be positive, got {}", self.recording.fps));
+        }
+        Ok(())
+    }
+}
+
+fn find_config_file() -> Option<PathBuf> {
+    let exe = std::env::current_exe().ok();
+    config_file_candidates(|name| std::env::var_os(name), exe.as_deref())
+        .into_iter()
+        .find(|path| path.is_file())
+}
+
+// Rutas donde se busca la configuración, en orden de prioridad
+fn config_file_candidates(var: impl Fn(&str) -> Option<OsString>, exe: Option<&Path>) -> Vec<PathBuf> {
+    let beside_executable = exe.and_then(Path::parent).map(|dir| dir.join(CONFIG_FILE_NAME));
+    // XDG pide ignorar rutas relativas
+    let user_config_dir = var("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| var("HOME").map(|home| Path::new(&home).join(".config")));
+    let user_config = user_config_dir.map(|dir| dir.join("ray_casting").join(USER_CONFIG_FILE_NAME));
+    // `cargo run` define CARGO_MANIFEST_DIR aunque se ejecute desde otro directorio
+    let crate_dir = var("CARGO_MANIFEST_DIR").map(|dir| Path::new(&dir).join(CONFIG_FILE_NAME));
+
+    std::iter::once(PathBuf::from(CONFIG_FILE_NAME))
+        .chain(beside_executable)
+        .chain(user_config)
+        .chain(crate_dir)
+        .collect()
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("invalid number: {}", value))
+}
+
+fn filter<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Filter, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    Filter::parse(&name)
+        .ok_or_else(|| D::Error::custom(format!("unknown filter '{}', expected nearest or bilinear", name)))
+}
+
+fn backend<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Backend, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    Backend::parse(&name)
+        .ok_or_else(|| D::Error::custom(format!("unknown backend '{}', expected window, terminal or ascii", name)))
+}
+
+fn recording_format<'de, D: Deserializer<'de>>(deserializer: D) -> Result<RecordingFormat, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    RecordingFormat::parse(&name)
+        .ok_or_else(|| D::Error::custom(format!("unknown recording format '{}', expected png, bmp or gif", name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        std::iter::once("ray_casting").chain(list.iter().copied()).map(String::from).collect()
+    }
+
+    #[test]
+    fn missing_keys_fall_back_to_defaults() {
+        let config = Config::parse("[display]\nwidth = 640\n\n[audio]\nsfx_volume = 0.5\n").unwrap();
+        assert_eq!(config.display.width, 640);
+        assert_eq!(config.display.height, DisplayConfig::default().height);
+        assert_eq!(config.audio.sfx_volume, 0.5);
+        assert_eq!(config.player, PlayerConfig::default());
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn shipped_config_matches_the_defaults() {
+        let config = Config::load(Path::new(env!("CARGO_MANIFEST_DIR")).join(CONFIG_FILE_NAME)).unwrap();
+        assert_eq!(config.display, DisplayConfig::default());
+        assert_eq!(config.player, PlayerConfig::default());
+        assert_eq!(config.audio, AudioConfig::default());
+        assert_eq!(config.recording, RecordingConfig::default());
+    }
+
+    #[test]
+    fn rejects_unknown_keys_and_invalid_values() {
+        assert!(Config::parse("[display]\nwidht = 640\n").is_err());
+        assert!(Config::parse("[display]\nfilter = \"cubic\"\n").is_err());
+        assert!(Config::parse("[display]\nbackend = \"opengl\"\n").is_err());
+        assert!(Config::parse("[display]\nrender_scale = 2.0\n").is_err());
+        assert!(Config::parse("[player]\nfov = 0\n").is_err());
+        assert!(Config::parse("[player]\nmouse_smoothing = 1.0\n").is_err());
+        assert!(Config::parse("[player]\nmouse_sensitivity = nan\n").is_err());
+        assert!(Config::parse("[player]\npitch_sensitivity = inf\n").is_err());
+        for fps in ["nan", "inf", "-inf", "0.0", "-1.0"] {
+            assert!(Config::parse(&format!("[recording]\nfps = {}\n", fps)).is_err(), "fps = {}", fps);
+        }
+    }
+
+    #[test]
+    fn command_line_overrides_the_file() {
+        let dir = std::env::temp_dir().join(format!("ray_casting_config_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("game.toml");
+        std::fs::write(&file, "[display]\nwidth = 640\nheight = 480\n\n[paths]\nasset_root = \"data\"\nsearch_paths = [\"shared\"]\n").unwrap();
+
+        let command = Config::from_args(&args(&[
+            "--config", &file.to_string_lossy(), "--size", "320x200", "--fov", "75", "--no-audio",
+            "--search-path", "extra", "--backend", "ascii", "--threads", "3",
+            "--pitch-sensitivity", "0.002",
+        ]))
+        .unwrap();
+        let Command::Run(config) = command else { panic!("expected a configuration") };
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!((config.display.width, config.display.height), (320, 200));
+        assert_eq!(config.player.fov, 75.0);
+        assert!(!config.audio.enabled);
+        assert_eq!(config.display.backend, Backend::Ascii);
+        assert_eq!(config.display.threads, 3);
+        assert_eq!(config.player.pitch_sensitivity, 0.002);
+        assert_eq!(config.asset_roots(), vec![dir.join("data"), dir.join("shared"), PathBuf::from("extra")]);
+    }
+
+    #[test]
+    fn config_file_search_order() {
+        let dir = std::env::temp_dir().join(format!("ray_casting_config_search_{}", std::process::id()));
+        let (exe_dir, xdg, home, manifest) = (dir.join("bin"), dir.join("xdg"), dir.join("home"), dir.join("crate"));
+        let env = |xdg_value: Option<PathBuf>| {
+            let (home, manifest) = (home.clone(), manifest.clone());
+            move |name: &str| match name {
+                "XDG_CONFIG_HOME" => xdg_value.clone().map(OsString::from),
+                "HOME" => Some(home.clone().into_os_string()),
+                "CARGO_MANIFEST_DIR" => Some(manifest.clone().into_os_string()),
+                _ => None,
+            }
+        };
+        let exe = exe_dir.join("ray_casting");
+
+        let candidates = config_file_candidates(env(Some(xdg.clone())), Some(&exe));
+        let expected = vec![
+            PathBuf::from(CONFIG_FILE_NAME),
+            exe_dir.join(CONFIG_FILE_NAME),
+            xdg.join("ray_casting/config.toml"),
+            manifest.join(CONFIG_FILE_NAME),
+        ];
+        assert_eq!(candidates, expected);
+        // Sin XDG_CONFIG_HOME, o con una ruta relativa, se usa ~/.config
+        let home_config = home.join(".config/ray_casting/config.toml");
+        assert_eq!(config_file_candidates(env(None), Some(&exe))[2], home_config);
+        assert_eq!(config_file_candidates(env(Some(PathBuf::from("relative"))), None)[1], home_config);
+
+        // Se toma el primero que existe: sin archivo junto al ejecutable gana el del usuario
+        let first_existing = || candidates[1..].iter().find(|path| path.is_file()).cloned();
+        std::fs::create_dir_all(xdg.join("ray_casting")).unwrap();
+        std::fs::create_dir_all(&manifest).unwrap();
+        std::fs::write(&expected[3], "").unwrap();
+        assert_eq!(first_existing(), Some(expected[3].clone()));
+        std::fs::write(&expected[2], "").unwrap();
+        assert_eq!(first_existing(), Some(expected[2].clone()));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn help_and_unknown_options() {
+        assert!(matches!(Config::from_args(&args(&["--help"])), Ok(Command::Help)));
+        assert!(Config::from_args(&args(&["--fullscreen"])).is_err());
+    }
+}
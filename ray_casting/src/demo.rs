@@ -0,0 +1,323 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use nalgebra_glm::Vec2;
+use crate::player::{step, InputState, Player};
+use crate::render::block_size;
+
+const DEMO_HEADER: &str = "# ray_casting demo v2";
+// Las grabaciones v1 no tienen cabeceo: sus ticks usan solo FBLR y mouse_dx
+const DEMO_HEADER_V1: &str = "# ray_casting demo v1";
+
+// Grabación de la entrada tick a tick. Repetirla desde la misma pose inicial reproduce
+// exactamente la misma trayectoria, porque `process_event` solo depende de la entrada y de dt.
+//
+// Formato de texto, una línea por registro:
+//   seed <u64>
+//   map <ruta del laberinto, tal como se buscó en los recursos>
+//   start <x> <y> <ángulo> <fov> <cabeceo> <sensibilidad del mouse> <sensibilidad del cabeceo>
+//   tick <dt> <acciones FBLRUD, '-' si no está activa> <mouse_dx> <mouse_dy>
+#[derive(Debug, Clone, PartialEq)]
+pub struct Demo {
+    // Semilla para los sistemas aleatorios, para que la repetición use la misma
+    pub seed: u64,
+    // Laberinto sobre el que se grabó; las colisiones dependen de él
+    pub map: Option<PathBuf>,
+    pub start_pos: Vec2,
+    pub start_angle: f32,
+    pub start_fov: f32,
+    pub start_pitch: f32,
+    // Los ticks guardan el mouse en píxeles, así que la sensibilidad es parte de la pose
+    pub start_sensitivity: f32,
+    pub start_pitch_sensitivity: f32,
+    pub ticks: Vec<(f32, InputState)>,
+}
+
+impl Demo {
+    pub fn new(seed: u64, player: &Player) -> Demo {
+        Demo {
+            seed,
+            map: None,
+            start_pos: player.pos,
+            start_angle: player.a,
+            start_fov: player.fov,
+            start_pitch: player.pitch,
+            start_sensitivity: player.mouse_sensitivity,
+            start_pitch_sensitivity: player.mouse_pitch_sensitivity,
+            ticks: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, dt: f32, input: InputState) {
+        self.ticks.push((dt, input));
+    }
+
+    // Jugador en la pose inicial de la grabación
+    pub fn spawn_player(&self) -> Player {
+        let mut player = Player::new(self.start_pos, self.start_angle);
+        player.fov = self.start_fov;
+        player.pitch = self.start_pitch;
+        player.mouse_sensitivity = self.start_sensitivity;
+        player.mouse_pitch_sensitivity = self.start_pitch_sensitivity;
+        player
+    }
+
+    // Aplica todos los ticks sin ventana sobre el mismo laberinto y devuelve el jugador final
+    pub fn replay(&self, maze: &[Vec<char>]) -> Player {
+        let mut player = self.spawn_player();
+        let block_size = block_size(maze);
+        for (dt, input) in &self.ticks {
+            step(input, *dt, &mut player, maze, block_size);
+        }
+        player
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> std::io::Result<Demo> {
+        Demo::read_from(File::open(path)?)
+    }
+
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        writeln!(writer, "{}", DEMO_HEADER)?;
+        writeln!(writer, "seed {}", self.seed)?;
+        if let Some(map) = &self.map {
+            writeln!(writer, "map {}", map.display())?;
+        }
+        writeln!(
+            writer,
+            "start {} {} {} {} {} {} {}",
+            self.start_pos.x,
+            self.start_pos.y,
+            self.start_angle,
+            self.start_fov,
+            self.start_pitch,
+            self.start_sensitivity,
+            self.start_pitch_sensitivity
+        )?;
+        for (dt, input) in &self.ticks {
+            let flag = |active: bool, c: char| if active { c } else { '-' };
+            writeln!(
+                writer,
+                "tick {} {}{}{}{}{}{} {} {}",
+                dt,
+                flag(input.forward, 'F'),
+                flag(input.backward, 'B'),
+                flag(input.turn_left, 'L'),
+                flag(input.turn_right, 'R'),
+                flag(input.look_up, 'U'),
+                flag(input.look_down, 'D'),
+                input.mouse_dx,
+                input.mouse_dy
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn read_from<R: Read>(reader: R) -> std::io::Result<Demo> {
+        let mut lines = BufReader::new(reader).lines();
+        let header = lines.next().transpose()?;
+        if header.as_deref() != Some(DEMO_HEADER) && header.as_deref() != Some(DEMO_HEADER_V1) {
+            return Err(invalid_data("not a ray_casting demo file"));
+        }
+
+        // Los campos que faltan en grabaciones anteriores toman los valores de `Player::new`
+        let mut demo = Demo::new(0, &Player::new(Vec2::new(0.0, 0.0), 0.0));
+
+        for line in lines {
+            let line = line?;
+            // La ruta puede tener espacios: ocupa el resto de la línea
+            if let Some(map) = line.strip_prefix("map ") {
+                demo.map = Some(PathBuf::from(map));
+                continue;
+            }
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields.as_slice() {
+                [] => {}
+                ["seed", seed] => demo.seed = parse(seed)?,
+                ["start", x, y, angle, fov, rest @ ..] if rest.len() <= 3 => {
+                    demo.start_pos = Vec2::new(parse(x)?, parse(y)?);
+                    demo.start_angle = parse(angle)?;
+                    demo.start_fov = parse(fov)?;
+                    if let Some(pitch) = rest.first() {
+                        demo.start_pitch = parse(pitch)?;
+                    }
+                    if let Some(sensitivity) = rest.get(1) {
+                        demo.start_sensitivity = parse(sensitivity)?;
+                    }
+                    // Antes el cabeceo usaba la misma sensibilidad que el giro
+                    demo.start_pitch_sensitivity = match rest.get(2) {
+                        Some(sensitivity) => parse(sensitivity)?,
+                        None if rest.len() == 2 => demo.start_sensitivity,
+                        None => demo.start_pitch_sensitivity,
+                    };
+                }
+                ["tick", dt, actions, mouse_dx, mouse_dy @ ..] if mouse_dy.len() <= 1 => {
+                    let actions = actions.as_bytes();
+                    if actions.len() != 4 && actions.len() != 6 {
+                        return Err(invalid_data(&format!("invalid demo actions: {}", line)));
+                    }
+                    let input = InputState {
+                        forward: actions[0] == b'F',
+                        backward: actions[1] == b'B',
+                        turn_left: actions[2] == b'L',
+                        turn_right: actions[3] == b'R',
+                        look_up: actions.get(4) == Some(&b'U'),
+                        look_down: actions.get(5) == Some(&b'D'),
+                        mouse_dx: parse(mouse_dx)?,
+                        mouse_dy: mouse_dy.first().map_or(Ok(0.0), |dy| parse(dy))?,
+                    };
+                    demo.ticks.push((parse(dt)?, input));
+                }
+                _ => return Err(invalid_data(&format!("invalid demo line: {}", line))),
+            }
+        }
+
+        Ok(demo)
+    }
+}
+
+// Reproduce una grabación tick a tick, p. ej. dentro del bucle del juego
+pub struct DemoPlayback {
+    demo: Demo,
+    next_tick: usize,
+}
+
+impl DemoPlayback {
+    pub fn new(demo: Demo) -> DemoPlayback {
+        DemoPlayback { demo, next_tick: 0 }
+    }
+
+    pub fn spawn_player(&self) -> Player {
+        self.demo.spawn_player()
+    }
+
+    // Laberinto en el que se grabó, si la grabación lo guarda
+    pub fn map(&self) -> Option<&Path> {
+        self.demo.map.as_deref()
+    }
+
+    // Siguiente tick grabado, o None al terminar
+    pub fn next_tick(&mut self) -> Option<(f32, InputState)> {
+        let tick = self.demo.ticks.get(self.next_tick).copied();
+        self.next_tick += 1;
+        tick
+    }
+}
+
+fn parse<T: std::str::FromStr>(value: &str) -> std::io::Result<T> {
+    value
+        .parse()
+        .map_err(|_| invalid_data(&format!("invalid number in demo: {}", value)))
+}
+
+fn invalid_data(message: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::load_maze;
+
+    fn fixture(name: &str) -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name)
+    }
+
+    fn corridor() -> Vec<Vec<char>> {
+        load_maze(&fixture("corridor.txt").to_string_lossy()).unwrap()
+    }
+
+    fn scripted_demo() -> Demo {
+        let player = Player::new(Vec2::new(114.0, 114.0), 0.0);
+        let mut demo = Demo::new(42, &player);
+        demo.map = Some(PathBuf::from("levels/with spaces/corridor.txt"));
+        for tick in 0..240 {
+            let input = InputState {
+                forward: tick % 3 != 0,
+                backward: tick % 17 == 0,
+                turn_left: (60..90).contains(&tick),
+                turn_right: (150..170).contains(&tick),
+                look_up: (100..110).contains(&tick),
+                look_down: (200..205).contains(&tick),
+                mouse_dx: if tick % 20 == 0 { 3.5 } else { 0.0 },
+                mouse_dy: if tick % 25 == 0 { -2.0 } else { 0.0 },
+            };
+            // dt irregular, como en un bucle real
+            demo.push(1.0 / 60.0 + (tick % 5) as f32 * 0.0007, input);
+        }
+        demo
+    }
+
+    #[test]
+    fn save_and_load_round_trips_exactly() {
+        let demo = scripted_demo();
+        let mut bytes = Vec::new();
+        demo.write_to(&mut bytes).unwrap();
+        let loaded = Demo::read_from(bytes.as_slice()).unwrap();
+        assert_eq!(loaded, demo);
+    }
+
+    #[test]
+    fn replay_from_file_reproduces_the_trajectory() {
+        let maze = corridor();
+        let demo = scripted_demo();
+        let expected = demo.replay(&maze);
+
+        let mut bytes = Vec::new();
+        demo.write_to(&mut bytes).unwrap();
+        let replayed = Demo::read_from(bytes.as_slice()).unwrap().replay(&maze);
+
+        assert_eq!(replayed.pos, expected.pos);
+        assert_eq!(replayed.a.to_bits(), expected.a.to_bits());
+        assert_eq!(replayed.pitch.to_bits(), expected.pitch.to_bits());
+    }
+
+    #[test]
+    fn older_demos_pitch_with_the_turn_sensitivity() {
+        let demo = Demo::read_from("# ray_casting demo v2\nstart 114 114 0 1 0 0.01\n".as_bytes()).unwrap();
+        assert_eq!(demo.spawn_player().mouse_pitch_sensitivity, 0.01);
+        let demo = Demo::read_from("# ray_casting demo v2\nstart 114 114 0 1\n".as_bytes()).unwrap();
+        assert_eq!(demo.start_pitch_sensitivity, Player::new(Vec2::new(0.0, 0.0), 0.0).mouse_pitch_sensitivity);
+    }
+
+    #[test]
+    fn replay_uses_the_recorded_mouse_sensitivity() {
+        let maze = corridor();
+        let mut player = Player::new(Vec2::new(114.0, 114.0), 0.0);
+        player.mouse_sensitivity = 0.01;
+        player.mouse_pitch_sensitivity = 0.003;
+        let mut demo = Demo::new(7, &player);
+        demo.ticks = scripted_demo().ticks;
+        let expected = demo.replay(&maze);
+
+        let mut bytes = Vec::new();
+        demo.write_to(&mut bytes).unwrap();
+        let loaded = Demo::read_from(bytes.as_slice()).unwrap();
+        assert_eq!(loaded.spawn_player().mouse_sensitivity, 0.01);
+        assert_eq!(loaded.spawn_player().mouse_pitch_sensitivity, 0.003);
+        let replayed = loaded.replay(&maze);
+        assert_eq!(replayed.a.to_bits(), expected.a.to_bits());
+        assert_eq!(replayed.pitch.to_bits(), expected.pitch.to_bits());
+        // Con la sensibilidad por defecto el mouse gira otro ángulo
+        assert_ne!(replayed.a, scripted_demo().replay(&maze).a);
+    }
+
+    #[test]
+    fn fixture_demo_ends_at_recorded_pose() {
+        // La grabación es v1, anterior al cabeceo
+        let player = Demo::load(fixture("corridor_walk.demo")).unwrap().replay(&corridor());
+        assert!((player.pos.x - 674.0).abs() < 0.5, "x = {}", player.pos.x);
+        assert!((player.pos.y - 114.0).abs() < 0.5, "y = {}", player.pos.y);
+        assert_eq!(player.pitch, 0.0);
+    }
+
+    #[test]
+    fn rejects_files_without_header() {
+        assert!(Demo::read_from("tick 0.016 F--- 0".as_bytes()).is_err());
+    }
+}
@@ -1,58 +1,329 @@
+use std::fmt;
+use std::path::Path;
+use image::{Rgb, RgbImage};
 use crate::color::Color;
-use crate::bmp::write_bmp_file;
-use image::Rgba;
+use crate::bmp::{read_bmp_file, write_bmp_file};
+
+// Qué hacer con las escrituras fuera del búfer en `set_pixel`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ClipPolicy {
+    // Se descartan sin avisar
+    Silent,
+    // Se cuentan y `end_frame` informa el total una vez por cuadro
+    Debug,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct OutOfBounds {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl fmt::Display for OutOfBounds {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "pixel ({}, {}) is outside the {}x{} framebuffer", self.x, self.y, self.width, self.height)
+    }
+}
+
+impl std::error::Error for OutOfBounds {}
 
 pub struct FrameBuffer {
     pub width: usize,
     pub height: usize,
-    pub buffer: Vec<Color>,
+    // Píxeles empaquetados como 0x00RRGGBB, el formato que espera minifb
+    pub buffer: Vec<u32>,
     pub background_color: Color,
     pub current_color: Color,
+    pub clip_policy: ClipPolicy,
+    out_of_bounds_writes: usize,
 }
 
 impl FrameBuffer {
     pub fn new(width: usize, height: usize) -> FrameBuffer {
         let default_color = Color::new(255, 255, 255);
-        let buffer = vec![default_color; width * height];
+        let buffer = vec![default_color.to_hex(); width * height];
         FrameBuffer {
             width,
             height,
             buffer,
             background_color: default_color,
             current_color: default_color,
+            clip_policy: ClipPolicy::Silent,
+            out_of_bounds_writes: 0,
         }
     }
 
+    // Carga un BMP (p. ej. una captura o una imagen de referencia); el alfa se descarta
+    pub fn from_bmp<P: AsRef<Path>>(file_path: P) -> std::io::Result<FrameBuffer> {
+        let image = read_bmp_file(file_path)?;
+        let mut framebuffer = FrameBuffer::new(image.width, image.height);
+        for (pixel, value) in framebuffer.buffer.iter_mut().zip(&image.pixels) {
+            *pixel = value & 0x00FF_FFFF;
+        }
+        Ok(framebuffer)
+    }
+
     pub fn clear(&mut self) {
-        self.buffer.fill(self.background_color);
+        self.buffer.fill(self.background_color.to_hex());
     }
 
     pub fn set_pixel(&mut self, x: usize, y: usize, color: Color) {
+        if self.try_set_pixel(x, y, color).is_err() && self.clip_policy == ClipPolicy::Debug {
+            self.out_of_bounds_writes += 1;
+        }
+    }
+
+    pub fn try_set_pixel(&mut self, x: usize, y: usize, color: Color) -> Result<(), OutOfBounds> {
         if x < self.width && y < self.height {
-            self.buffer[self.width * y + x] = color;
+            self.buffer[self.width * y + x] = color.to_hex();
+            Ok(())
         } else {
-            eprintln!("Warning: Trying to set pixel out of bounds at ({}, {})", x, y);
+            Err(OutOfBounds { x, y, width: self.width, height: self.height })
+        }
+    }
+
+    pub fn set_clip_policy(&mut self, policy: ClipPolicy) {
+        self.clip_policy = policy;
+        self.out_of_bounds_writes = 0;
+    }
+
+    // Cierra el cuadro actual: en modo Debug informa una sola vez las escrituras fuera de rango
+    pub fn end_frame(&mut self) -> usize {
+        let count = std::mem::take(&mut self.out_of_bounds_writes);
+        if count > 0 {
+            eprintln!("Warning: {} out-of-bounds pixel writes this frame", count);
         }
+        count
     }
 
     pub fn draw_rectangle(&mut self, x: usize, y: usize, width: usize, height: usize) {
         // Asegúrate de que el rectángulo esté dentro del búfer
+        if x >= self.width || y >= self.height {
+            return;
+        }
         let x_end = (x + width).min(self.width);
         let y_end = (y + height).min(self.height);
-    
+
         for j in y..y_end {
             let start_index = self.width * j + x;
             let end_index = start_index + (x_end - x);
-            self.buffer[start_index..end_index].fill(self.current_color);
+            self.buffer[start_index..end_index].fill(self.current_color.to_hex());
+        }
+    }
+
+    pub fn draw_rectangle_outline(&mut self, x: i32, y: i32, width: i32, height: i32) {
+        if width <= 0 || height <= 0 {
+            return;
+        }
+        // En i64 para que los bordes no desborden; los lados solo recorren las filas visibles
+        let (x, y) = (x as i64, y as i64);
+        let (x_end, y_end) = (x + width as i64 - 1, y + height as i64 - 1);
+        let (left, right) = (self.clamp_x(x), self.clamp_x(x_end));
+        for row in [y, y_end] {
+            if (0..self.height as i64).contains(&row) {
+                self.draw_span(left, right, row as i32);
+            }
+        }
+        for j in (y + 1).max(0)..y_end.min(self.height as i64) {
+            self.plot(left, j as i32);
+            self.plot(right, j as i32);
         }
     }
+
+    // Línea de Bresenham con el color actual
+    pub fn draw_line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32) {
+        let Some((x0, y0, x1, y1)) = self.clip_line(x0 as f64, y0 as f64, x1 as f64, y1 as f64) else {
+            return;
+        };
+        let (mut x, mut y) = (x0.round() as i32, y0.round() as i32);
+        let (x1, y1) = (x1.round() as i32, y1.round() as i32);
+
+        let dx = (x1 - x).abs();
+        let dy = -(y1 - y).abs();
+        let sx = if x < x1 { 1 } else { -1 };
+        let sy = if y < y1 { 1 } else { -1 };
+        let mut err = dx + dy;
+
+        loop {
+            self.plot(x, y);
+            if x == x1 && y == y1 {
+                break;
+            }
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                x += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                y += sy;
+            }
+        }
+    }
+
+    // Línea suavizada (algoritmo de Xiaolin Wu), mezclando el color actual con el fondo existente
+    pub fn draw_line_aa(&mut self, x0: f32, y0: f32, x1: f32, y1: f32) {
+        let Some(clipped) = self.clip_line(x0 as f64, y0 as f64, x1 as f64, y1 as f64) else {
+            return;
+        };
+        let (mut x0, mut y0, mut x1, mut y1) = (clipped.0 as f32, clipped.1 as f32, clipped.2 as f32, clipped.3 as f32);
+
+        let steep = (y1 - y0).abs() > (x1 - x0).abs();
+        if steep {
+            std::mem::swap(&mut x0, &mut y0);
+            std::mem::swap(&mut x1, &mut y1);
+        }
+        if x0 > x1 {
+            std::mem::swap(&mut x0, &mut x1);
+            std::mem::swap(&mut y0, &mut y1);
+        }
+
+        let dx = x1 - x0;
+        let gradient = if dx == 0.0 { 1.0 } else { (y1 - y0) / dx };
+
+        let plot = |fb: &mut FrameBuffer, x: i32, y: i32, coverage: f32| {
+            if steep {
+                fb.plot_blend(y, x, coverage);
+            } else {
+                fb.plot_blend(x, y, coverage);
+            }
+        };
+
+        // Extremos
+        let x_start = x0.round();
+        let y_start = y0 + gradient * (x_start - x0);
+        let gap = 1.0 - (x0 + 0.5).fract();
+        plot(self, x_start as i32, y_start.floor() as i32, (1.0 - y_start.fract()) * gap);
+        plot(self, x_start as i32, y_start.floor() as i32 + 1, y_start.fract() * gap);
+
+        let x_end = x1.round();
+        let y_end = y1 + gradient * (x_end - x1);
+        let gap = (x1 + 0.5).fract();
+        plot(self, x_end as i32, y_end.floor() as i32, (1.0 - y_end.fract()) * gap);
+        plot(self, x_end as i32, y_end.floor() as i32 + 1, y_end.fract() * gap);
+
+        // Tramo intermedio
+        let mut inter_y = y_start + gradient;
+        for x in (x_start as i32 + 1)..(x_end as i32) {
+            plot(self, x, inter_y.floor() as i32, 1.0 - inter_y.fract());
+            plot(self, x, inter_y.floor() as i32 + 1, inter_y.fract());
+            inter_y += gradient;
+        }
+    }
+
+    // Circunferencia por filas: se pintan los píxeles a menos de medio píxel del radio, lo que deja
+    // un contorno continuo. Solo se recorren las filas visibles, en i64 para que centro más radio
+    // no desborde.
+    pub fn draw_circle(&mut self, cx: i32, cy: i32, radius: i32) {
+        if radius < 0 {
+            return;
+        }
+        let (cx, cy, radius) = (cx as i64, cy as i64, radius as i64);
+        // Medio ancho de la fila `dy` dentro de un círculo de radio `r`; a² - b² como producto para no
+        // perder precisión con radios grandes
+        let half = |r: f64, dy: f64| ((r - dy) * (r + dy)).max(0.0).sqrt();
+        let (outer_r, inner_r) = (radius as f64 + 0.5, (radius as f64 - 0.5).max(0.0));
+        let top = (cy - radius).max(0);
+        let bottom = (cy + radius).min(self.height as i64 - 1);
+        for y in top..=bottom {
+            let dy = (y - cy).abs() as f64;
+            let outer = half(outer_r, dy).floor() as i64;
+            let inner = if dy < inner_r { half(inner_r, dy).ceil() as i64 } else { 0 };
+            if inner > outer {
+                continue;
+            }
+            self.draw_span(self.clamp_x(cx - outer), self.clamp_x(cx - inner), y as i32);
+            self.draw_span(self.clamp_x(cx + inner), self.clamp_x(cx + outer), y as i32);
+        }
+    }
+
+    pub fn fill_circle(&mut self, cx: i32, cy: i32, radius: i32) {
+        if radius < 0 {
+            return;
+        }
+        // En i64: radius * radius desborda i32 a partir de 46341, y solo se recorren las filas visibles
+        let (cx, cy, radius) = (cx as i64, cy as i64, radius as i64);
+        let r2 = radius * radius;
+        let top = (cy - radius).max(0);
+        let bottom = (cy + radius).min(self.height as i64 - 1);
+        for y in top..=bottom {
+            let dy = y - cy;
+            let half = ((r2 - dy * dy) as f64).sqrt() as i64;
+            self.draw_span(self.clamp_x(cx - half), self.clamp_x(cx + half), y as i32);
+        }
+    }
+
+    // Contorno cerrado que une todos los vértices
+    pub fn draw_polygon(&mut self, points: &[(i32, i32)]) {
+        if points.len() < 2 {
+            if let Some(&(x, y)) = points.first() {
+                self.plot(x, y);
+            }
+            return;
+        }
+        self.draw_polyline(points);
+        let (first, last) = (points[0], points[points.len() - 1]);
+        self.draw_line(last.0, last.1, first.0, first.1);
+    }
+
+    // Contorno abierto, útil para dibujar trayectorias
+    pub fn draw_polyline(&mut self, points: &[(i32, i32)]) {
+        for pair in points.windows(2) {
+            self.draw_line(pair[0].0, pair[0].1, pair[1].0, pair[1].1);
+        }
+    }
+
+    // Relleno por líneas de barrido con la regla par-impar, muestreando el centro de cada píxel
+    pub fn fill_polygon(&mut self, points: &[(i32, i32)]) {
+        if points.len() < 3 {
+            self.draw_polygon(points);
+            return;
+        }
+
+        let min_y = points.iter().map(|p| p.1).min().unwrap_or(0).max(0);
+        let max_y = points.iter().map(|p| p.1).max().unwrap_or(0).min(self.height as i32 - 1);
+        let mut crossings: Vec<f32> = Vec::with_capacity(points.len());
+
+        for y in min_y..=max_y {
+            let scan_y = y as f32 + 0.5;
+            crossings.clear();
+
+            for i in 0..points.len() {
+                let (ax, ay) = points[i];
+                let (bx, by) = points[(i + 1) % points.len()];
+                let (ay, by) = (ay as f32, by as f32);
+                if (ay <= scan_y && by > scan_y) || (by <= scan_y && ay > scan_y) {
+                    let t = (scan_y - ay) / (by - ay);
+                    crossings.push(ax as f32 + t * (bx - ax) as f32);
+                }
+            }
+
+            crossings.sort_by(|a, b| a.total_cmp(b));
+            for pair in crossings.chunks_exact(2) {
+                let x_start = (pair[0] - 0.5).ceil() as i32;
+                let x_end = (pair[1] - 0.5).ceil() as i32 - 1;
+                self.draw_span(x_start, x_end, y);
+            }
+        }
+    }
+
+    pub fn draw_triangle(&mut self, a: (i32, i32), b: (i32, i32), c: (i32, i32)) {
+        self.draw_polygon(&[a, b, c]);
+    }
+
+    pub fn fill_triangle(&mut self, a: (i32, i32), b: (i32, i32), c: (i32, i32)) {
+        self.fill_polygon(&[a, b, c]);
+    }
+
     pub fn set_background_color(&mut self, color: Color) {
         self.background_color = color;
     }
 
     pub fn get_color(&self, x: usize, y: usize) -> Color {
         if x < self.width && y < self.height {
-            self.buffer[self.width * y + x]
+            Color::from_hex(self.buffer[self.width * y + x])
         } else {
             self.background_color // Return background color if out of bounds
         }
@@ -62,13 +333,300 @@ impl FrameBuffer {
         self.current_color = color;
     }
 
-    pub fn write_to_bmp(&self, file_path: &str) -> std::io::Result<()> {
-        let buffer: Vec<u32> = self.buffer.iter().map(|c| c.to_hex()).collect();
-        write_bmp_file(file_path, &buffer, self.width, self.height)
+    pub fn write_to_bmp<P: AsRef<Path>>(&self, file_path: P) -> std::io::Result<()> {
+        write_bmp_file(file_path, &self.buffer, self.width, self.height)
     }
 
-    pub fn cast_buffer(&self) -> Vec<u32> {
-        self.buffer.iter().map(|color| color.to_hex()).collect()
+    pub fn to_rgb_image(&self) -> RgbImage {
+        RgbImage::from_fn(self.width as u32, self.height as u32, |x, y| {
+            let color = Color::from_hex(self.buffer[self.width * y as usize + x as usize]);
+            Rgb([color.r, color.g, color.b])
+        })
+    }
+
+    pub fn write_to_png<P: AsRef<Path>>(&self, file_path: P) -> image::ImageResult<()> {
+        self.to_rgb_image().save_with_format(file_path, image::ImageFormat::Png)
+    }
+
+    // Vista del búfer lista para `Window::update_with_buffer`, sin copias
+    pub fn as_u32_slice(&self) -> &[u32] {
+        &self.buffer
+    }
+
+    // Pinta un píxel con el color actual; las coordenadas fuera del búfer se ignoran
+    fn plot(&mut self, x: i32, y: i32) {
+        if x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height {
+            let index = self.width * y as usize + x as usize;
+            self.buffer[index] = self.current_color.to_hex();
+        }
+    }
+
+    fn plot_blend(&mut self, x: i32, y: i32, coverage: f32) {
+        if x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height {
+            let index = self.width * y as usize + x as usize;
+            self.buffer[index] = Color::from_hex(self.buffer[index]).lerp(self.current_color, coverage).to_hex();
+        }
+    }
+
+    // Lleva una columna lejana justo afuera del búfer, donde `plot` y `draw_span` la descartan
+    fn clamp_x(&self, x: i64) -> i32 {
+        x.clamp(-1, self.width as i64) as i32
+    }
+
+    // Tramo horizontal [x0, x1] recortado al búfer
+    fn draw_span(&mut self, x0: i32, x1: i32, y: i32) {
+        if y < 0 || y as usize >= self.height || self.width == 0 {
+            return;
+        }
+        let (x0, x1) = if x0 <= x1 { (x0, x1) } else { (x1, x0) };
+        let x_start = x0.max(0);
+        let x_end = x1.min(self.width as i32 - 1);
+        if x_start > x_end {
+            return;
+        }
+        let row = self.width * y as usize;
+        self.buffer[row + x_start as usize..=row + x_end as usize].fill(self.current_color.to_hex());
+    }
+
+    // Recorta el segmento al rectángulo del búfer (Liang-Barsky); None si queda completamente fuera.
+    // En f64 para que coordenadas i32 lejanas no se redondeen hacia adentro del búfer.
+    fn clip_line(&self, x0: f64, y0: f64, x1: f64, y1: f64) -> Option<(f64, f64, f64, f64)> {
+        if self.width == 0 || self.height == 0 {
+            return None;
+        }
+        let (x_max, y_max) = ((self.width - 1) as f64, (self.height - 1) as f64);
+        let (dx, dy) = (x1 - x0, y1 - y0);
+        let mut t0: f64 = 0.0;
+        let mut t1: f64 = 1.0;
+
+        for (p, q) in [(-dx, x0), (dx, x_max - x0), (-dy, y0), (dy, y_max - y0)] {
+            if p == 0.0 {
+                if q < 0.0 {
+                    return None;
+                }
+            } else {
+                let t = q / p;
+                if p < 0.0 {
+                    t0 = t0.max(t);
+                } else {
+                    t1 = t1.min(t);
+                }
+            }
+        }
+
+        if t0 > t1 {
+            return None;
+        }
+        Some((x0 + t0 * dx, y0 + t0 * dy, x0 + t1 * dx, y0 + t1 * dy))
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INK: Color = Color { r: 255, g: 255, b: 255 };
+
+    fn canvas(width: usize, height: usize) -> FrameBuffer {
+        let mut framebuffer = FrameBuffer::new(width, height);
+        framebuffer.set_background_color(Color::new(0, 0, 0));
+        framebuffer.clear();
+        framebuffer.set_current_color(INK);
+        framebuffer
+    }
+
+    // Píxeles pintados con el color actual, en orden de fila
+    fn painted(framebuffer: &FrameBuffer) -> Vec<(usize, usize)> {
+        let mut pixels = Vec::new();
+        for y in 0..framebuffer.height {
+            for x in 0..framebuffer.width {
+                if framebuffer.get_color(x, y) == INK {
+                    pixels.push((x, y));
+                }
+            }
+        }
+        pixels
+    }
+
+    fn row(y: usize, xs: std::ops::RangeInclusive<usize>) -> Vec<(usize, usize)> {
+        xs.map(|x| (x, y)).collect()
+    }
+
+    #[test]
+    fn lines_include_both_endpoints() {
+        let mut framebuffer = canvas(8, 6);
+        framebuffer.draw_line(1, 1, 6, 4);
+        let pixels = painted(&framebuffer);
+        assert_eq!(pixels.len(), 6);
+        assert_eq!((pixels[0], pixels[5]), ((1, 1), (6, 4)));
+
+        let mut framebuffer = canvas(8, 6);
+        framebuffer.draw_line(7, 2, 0, 2);
+        assert_eq!(painted(&framebuffer), row(2, 0..=7));
+    }
+
+    #[test]
+    fn lines_are_clipped_at_each_edge() {
+        let mut framebuffer = canvas(8, 6);
+        framebuffer.draw_line(-5, 2, 20, 2);
+        assert_eq!(painted(&framebuffer), row(2, 0..=7));
+
+        let mut framebuffer = canvas(8, 6);
+        framebuffer.draw_line(3, 10, 3, -10);
+        assert_eq!(painted(&framebuffer), (0..6).map(|y| (3, y)).collect::<Vec<_>>());
+
+        let mut framebuffer = canvas(8, 6);
+        framebuffer.draw_line(-2, -2, 10, 10);
+        assert_eq!(painted(&framebuffer), (0..6).map(|i| (i, i)).collect::<Vec<_>>());
+
+        // Justo fuera de la izquierda, la derecha, arriba y abajo
+        let mut framebuffer = canvas(8, 6);
+        framebuffer.draw_line(-1, 0, -1, 5);
+        framebuffer.draw_line(8, 0, 8, 5);
+        framebuffer.draw_line(0, -1, 7, -1);
+        framebuffer.draw_line(0, 6, 7, 6);
+        framebuffer.draw_line(i32::MIN, i32::MIN, -1, -1);
+        assert!(painted(&framebuffer).is_empty());
+    }
+
+    #[test]
+    fn zero_length_lines_paint_a_single_pixel() {
+        let mut framebuffer = canvas(8, 6);
+        framebuffer.draw_line(3, 3, 3, 3);
+        framebuffer.draw_line(20, 20, 20, 20);
+        assert_eq!(painted(&framebuffer), vec![(3, 3)]);
+
+        let mut framebuffer = canvas(8, 6);
+        framebuffer.draw_line_aa(2.0, 2.0, 2.0, 2.0);
+        let touched: Vec<usize> = (0..framebuffer.buffer.len()).filter(|&i| framebuffer.buffer[i] != 0).collect();
+        assert_eq!(touched, vec![2 * 8 + 2]);
+    }
+
+    #[test]
+    fn antialiased_lines_blend_their_ends_and_stay_inside() {
+        let mut framebuffer = canvas(8, 6);
+        framebuffer.draw_line_aa(1.0, 2.0, 6.0, 2.0);
+        assert_eq!(painted(&framebuffer), row(2, 2..=5));
+        for x in [1, 6] {
+            let end = framebuffer.get_color(x, 2);
+            assert!(end.r > 0 && end.r < 255, "end ({}, 2) = {:?}", x, end);
+        }
+
+        let mut framebuffer = canvas(8, 6);
+        framebuffer.draw_line_aa(-10.0, 2.0, 20.0, 2.0);
+        framebuffer.draw_line_aa(-10.0, -3.0, 20.0, -3.0);
+        framebuffer.draw_line_aa(3.5, -10.0, 3.5, 20.0);
+        let outside = (0..6).filter(|&y| y != 2).flat_map(|y| [0, 1, 2, 5, 6, 7].map(|x| (x, y)));
+        assert!(outside.into_iter().all(|(x, y)| framebuffer.get_color(x, y) == Color::new(0, 0, 0)));
+        assert_eq!(framebuffer.get_color(4, 2), INK);
+    }
+
+    #[test]
+    fn filled_circles_are_symmetric_and_clipped() {
+        let mut framebuffer = canvas(8, 8);
+        framebuffer.fill_circle(3, 3, 2);
+        let expected = [row(1, 3..=3), row(2, 2..=4), row(3, 1..=5), row(4, 2..=4), row(5, 3..=3)].concat();
+        assert_eq!(painted(&framebuffer), expected);
+
+        let mut framebuffer = canvas(8, 8);
+        framebuffer.fill_circle(0, 0, 2);
+        assert_eq!(painted(&framebuffer), [row(0, 0..=2), row(1, 0..=1), row(2, 0..=0)].concat());
+    }
+
+    #[test]
+    fn circle_outlines_are_continuous_and_clipped() {
+        let mut framebuffer = canvas(8, 8);
+        framebuffer.draw_circle(3, 3, 2);
+        let sides = [1, 5].map(|x| [(x, 2), (x, 3), (x, 4)]).concat();
+        let mut expected = [row(1, 2..=4), sides, row(5, 2..=4)].concat();
+        expected.sort_by_key(|&(x, y)| (y, x));
+        assert_eq!(painted(&framebuffer), expected);
+
+        // Un círculo enorme que cruza el búfer pinta solo las filas visibles
+        let mut framebuffer = canvas(8, 8);
+        framebuffer.draw_circle(3, -1_000_000_000 + 4, 1_000_000_000);
+        assert_eq!(painted(&framebuffer).len(), 8);
+        assert!(painted(&framebuffer).iter().all(|&(_, y)| y == 4));
+
+        // Con el búfer entero adentro no se pinta nada
+        let mut framebuffer = canvas(8, 8);
+        framebuffer.draw_circle(4, 4, i32::MAX);
+        assert!(painted(&framebuffer).is_empty());
+    }
+
+    #[test]
+    fn degenerate_circles() {
+        let mut framebuffer = canvas(8, 8);
+        framebuffer.fill_circle(4, 4, 0);
+        framebuffer.draw_circle(1, 1, 0);
+        framebuffer.fill_circle(4, 4, -1);
+        framebuffer.draw_circle(4, 4, -1);
+        framebuffer.fill_circle(i32::MAX, i32::MIN, 10);
+        assert_eq!(painted(&framebuffer), vec![(1, 1), (4, 4)]);
+
+        // Lejos del búfer o con centro y radio cerca de i32::MAX no se pinta nada ni se desborda
+        framebuffer.draw_circle(i32::MAX, i32::MAX, i32::MAX);
+        framebuffer.draw_circle(i32::MIN, 0, 10);
+        framebuffer.draw_rectangle_outline(i32::MAX, i32::MAX, i32::MAX, i32::MAX);
+        framebuffer.draw_rectangle_outline(i32::MIN, i32::MIN, 1, 1);
+        assert_eq!(painted(&framebuffer), vec![(1, 1), (4, 4)]);
+
+        // radius * radius no entra en un i32
+        let mut framebuffer = canvas(8, 8);
+        framebuffer.fill_circle(0, 0, 50_000);
+        assert_eq!(painted(&framebuffer).len(), 64);
+    }
+
+    #[test]
+    fn polygons_fill_pixel_centers_inside_the_outline() {
+        let mut framebuffer = canvas(8, 6);
+        framebuffer.fill_polygon(&[(1, 1), (5, 1), (5, 4), (1, 4)]);
+        assert_eq!(painted(&framebuffer), [row(1, 1..=4), row(2, 1..=4), row(3, 1..=4)].concat());
+
+        let mut framebuffer = canvas(8, 6);
+        framebuffer.fill_polygon(&[(-10, -10), (20, -10), (20, 20), (-10, 20)]);
+        assert_eq!(painted(&framebuffer).len(), 48);
+
+        let mut framebuffer = canvas(8, 6);
+        framebuffer.draw_polygon(&[(1, 1), (4, 1), (4, 4), (1, 4)]);
+        let pixels = painted(&framebuffer);
+        assert_eq!(pixels.len(), 12);
+        assert!(!pixels.contains(&(2, 2)));
+    }
+
+    #[test]
+    fn degenerate_polygons() {
+        let mut framebuffer = canvas(8, 6);
+        framebuffer.fill_polygon(&[]);
+        framebuffer.draw_polygon(&[]);
+        assert!(painted(&framebuffer).is_empty());
+
+        framebuffer.fill_polygon(&[(2, 3)]);
+        assert_eq!(painted(&framebuffer), vec![(2, 3)]);
+
+        let mut framebuffer = canvas(8, 6);
+        framebuffer.fill_polygon(&[(1, 1), (4, 1)]);
+        assert_eq!(painted(&framebuffer), row(1, 1..=4));
+
+        // Un triángulo sin área no tiene centros de píxel adentro; su contorno es una línea
+        let mut framebuffer = canvas(8, 6);
+        framebuffer.fill_triangle((1, 1), (3, 1), (6, 1));
+        assert!(painted(&framebuffer).is_empty());
+        framebuffer.draw_triangle((1, 1), (3, 1), (6, 1));
+        assert_eq!(painted(&framebuffer), row(1, 1..=6));
+    }
+
+    #[test]
+    fn rectangle_outlines_are_clipped() {
+        let mut framebuffer = canvas(8, 6);
+        framebuffer.draw_rectangle_outline(-1, -1, 4, 4);
+        assert_eq!(painted(&framebuffer), vec![(2, 0), (2, 1), (0, 2), (1, 2), (2, 2)]);
+
+        let mut framebuffer = canvas(8, 6);
+        framebuffer.draw_rectangle_outline(3, 3, 0, 5);
+        framebuffer.draw_rectangle_outline(3, 3, 5, -1);
+        assert!(painted(&framebuffer).is_empty());
+        framebuffer.draw_rectangle_outline(3, 3, 1, 1);
+        assert_eq!(painted(&framebuffer), vec![(3, 3)]);
+    }
+}
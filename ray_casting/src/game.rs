@@ -0,0 +1,396 @@
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use nalgebra_glm::Vec2;
+use rusttype::Font;
+use crate::assets::{AssetError, Assets, Map, Texture};
+use crate::audio::{Audio, Category, SoundEffect};
+use crate::blit::Filter;
+use crate::capture::{save_screenshot, timestamp};
+use crate::color::Color;
+use crate::config::Config;
+use crate::demo::{Demo, DemoPlayback};
+use crate::framebuffer::{ClipPolicy, FrameBuffer};
+use crate::music::level_playlist;
+use crate::platform::Platform;
+use crate::player::{step, InputState, MouseFilter, Player};
+use crate::positional::map_emitters;
+use crate::profiler::{Phase, Profiler};
+use crate::recorder::{Recorder, RecordingSummary};
+use crate::render::{block_size, render2d, render3d_threaded};
+
+// Textura de las paredes, buscada en las rutas de recursos
+pub const WALL_TEXTURE: &str = "assets/textura.png";
+// Distancia recorrida entre un paso y el siguiente
+const FOOTSTEP_DISTANCE: f32 = 60.0;
+// Pausa entre cuadros, unos 60 por segundo
+const FRAME_SLEEP: Duration = Duration::from_millis(16);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ViewMode {
+    Map2D,
+    View3D,
+}
+
+// Estado del juego y bucle principal, independiente de la plataforma que lo muestre
+pub struct Game {
+    pub player: Player,
+    pub mode: ViewMode,
+    pub audio: Audio,
+    pub mouse: MouseFilter,
+    maze: Map,
+    // Ruta con la que se cargó el laberinto, para guardarla en las grabaciones
+    map_path: PathBuf,
+    texture: Texture,
+    block_size: usize,
+    framebuffer: FrameBuffer,
+    // Búfer del tamaño de la ventana, solo si se renderiza a menor escala
+    screen: Option<FrameBuffer>,
+    filter: Filter,
+    render_threads: usize,
+    recorder: Recorder,
+    demo: Option<Demo>,
+    playback: Option<DemoPlayback>,
+    // Distancia desde el último paso y choque del tick anterior, para no repetir el golpe cada tick
+    distance_since_step: f32,
+    was_bumping: bool,
+    last_tick: Duration,
+    // Cálculo de FPS
+    last_update: Duration,
+    frame_count: u32,
+    // Tiempos por fase; el total de cada cuadro va de un inicio de cuadro al siguiente
+    profiler: Profiler,
+    profile_csv: Option<PathBuf>,
+    frame_start: Option<Duration>,
+    font: Option<Font<'static>>,
+}
+
+impl Game {
+    // Carga el nivel de la configuración y prepara el audio: volúmenes, efectos, música y emisores
+    pub fn new(config: &Config, assets: &mut Assets, mut audio: Audio) -> Game {
+        let maze_handle = assets.map_or_placeholder(&config.paths.map);
+        let texture_handle = assets.texture_or_placeholder(WALL_TEXTURE);
+        let maze = assets.map(maze_handle).clone();
+        let texture = assets.texture(texture_handle).clone();
+        // Sin la fuente, el perfilador muestra solo el gráfico
+        let font = assets.load_font("assets/Roboto-Black.ttf").ok().map(|handle| assets.font(handle).clone());
+        let block_size = block_size(&maze);
+
+        audio.set_volume(Category::Music, config.audio.music_volume);
+        audio.set_volume(Category::Sfx, config.audio.sfx_volume);
+        audio.set_volume(Category::Ui, config.audio.ui_volume);
+        audio.preload_effects(assets, Path::new("assets/sfx"));
+
+        start_level_audio(&mut audio, assets, &config.paths.map, &maze, block_size);
+
+        // Framebuffer a la resolución interna y, si hace falta, el búfer de la ventana
+        let (width, height) = config.render_size();
+        let mut framebuffer = FrameBuffer::new(width, height);
+        framebuffer.set_current_color(Color::new(50, 50, 100));
+        if config.debug.clip {
+            framebuffer.set_clip_policy(ClipPolicy::Debug);
+        }
+        let window_size = (config.display.width, config.display.height);
+        let screen = (window_size != (width, height)).then(|| FrameBuffer::new(window_size.0, window_size.1));
+
+        // Ajusta esto según el tamaño de bloque y la posición deseada
+        let mut player = Player::new(Vec2::new(250.0, 150.0), std::f32::consts::PI / 3.0);
+        player.fov = config.player.fov.to_radians();
+        player.mouse_sensitivity = config.player.mouse_sensitivity;
+        player.mouse_pitch_sensitivity = config.player.pitch_sensitivity;
+
+        let r = &config.recording;
+        Game {
+            player,
+            mode: ViewMode::Map2D,
+            audio,
+            mouse: MouseFilter::new(config.player.mouse_smoothing, config.player.invert_mouse_y),
+            maze,
+            map_path: config.paths.map.clone(),
+            texture,
+            block_size,
+            framebuffer,
+            screen,
+            filter: config.display.filter,
+            render_threads: config.render_threads(),
+            recorder: Recorder::new(r.format, r.fps, r.max_frames),
+            demo: None,
+            playback: None,
+            distance_since_step: 0.0,
+            was_bumping: false,
+            last_tick: Duration::ZERO,
+            last_update: Duration::ZERO,
+            frame_count: 0,
+            profiler: Profiler::new(config.debug.profiler),
+            profile_csv: config.debug.profile_csv.clone(),
+            frame_start: None,
+            font,
+        }
+    }
+
+    pub fn maze(&self) -> &Map {
+        &self.maze
+    }
+
+    // Cambia de laberinto: la música pasa a la lista del nuevo nivel con un fundido cruzado y los
+    // sonidos ambientales se reubican. El jugador queda donde estaba.
+    pub fn load_level(&mut self, assets: &mut Assets, map: &Path) -> Result<(), AssetError> {
+        let handle = assets.load_map(map)?;
+        self.maze = assets.map(handle).clone();
+        self.block_size = block_size(&self.maze);
+        self.map_path = map.to_path_buf();
+        self.audio.clear_emitters();
+        start_level_audio(&mut self.audio, assets, map, &self.maze, self.block_size);
+        Ok(())
+    }
+
+    // Reemplaza la entrada en vivo por una grabación, desde su pose inicial. Si la grabación guarda
+    // su laberinto y es otro, se carga antes, como en `--replay`; si no, se repetiría sobre otro mapa.
+    pub fn play_demo(&mut self, assets: &mut Assets, playback: DemoPlayback) -> Result<(), AssetError> {
+        if let Some(map) = playback.map().filter(|&map| map != self.map_path) {
+            self.load_level(assets, map)?;
+        }
+        self.player = playback.spawn_player();
+        self.playback = Some(playback);
+        Ok(())
+    }
+
+    // Empieza a grabar la entrada de cada tick desde la pose actual
+    pub fn record_demo(&mut self, seed: u64) {
+        let mut demo = Demo::new(seed, &self.player);
+        demo.map = Some(self.map_path.clone());
+        self.demo = Some(demo);
+    }
+
+    pub fn profiler(&self) -> &Profiler {
+        &self.profiler
+    }
+
+    // Grabación de la entrada, si se pidió con `record_demo`
+    pub fn take_demo(&mut self) -> Option<Demo> {
+        self.demo.take()
+    }
+
+    // Corre cuadros hasta que la plataforma se cierre o el jugador salga
+    pub fn run<P: Platform>(&mut self, platform: &mut P) -> Result<(), Box<dyn Error>> {
+        self.last_tick = platform.now();
+        self.last_update = platform.now();
+        while platform.is_open() {
+            if !self.frame(platform)? {
+                break;
+            }
+        }
+        if self.recorder.is_recording() {
+            report_recording(platform, self.recorder.stop());
+        }
+        if let Some(path) = self.profile_csv.clone() {
+            self.export_profile(platform, &path);
+        }
+        Ok(())
+    }
+
+    // Un cuadro completo: entrada, simulación, sonido, render y presentación.
+    // Devuelve false si el jugador pidió salir.
+    pub fn frame<P: Platform>(&mut self, platform: &mut P) -> Result<bool, Box<dyn Error>> {
+        let frame_start = platform.now();
+        if let Some(previous) = self.frame_start.replace(frame_start) {
+            self.profiler.end_frame(frame_start.saturating_sub(previous));
+        }
+
+        let start = Instant::now();
+        let frame_input = platform.poll_input();
+        self.profiler.record(Phase::Input, start.elapsed());
+        let actions = frame_input.actions;
+        if actions.quit {
+            return Ok(false);
+        }
+
+        if actions.toggle_view {
+            self.mode = match self.mode {
+                ViewMode::Map2D => ViewMode::View3D,
+                ViewMode::View3D => ViewMode::Map2D,
+            };
+        }
+
+        if actions.toggle_profiler {
+            self.profiler.visible = !self.profiler.visible;
+        }
+        if actions.export_profile {
+            let path = Path::new("profiles").join(format!("profile_{}.csv", timestamp()));
+            self.export_profile(platform, &path);
+        }
+
+        // Silenciar o reactivar el audio
+        if actions.toggle_mute {
+            self.audio.toggle_mute();
+            if self.audio.is_available() {
+                platform.show_message(&format!("Audio {}", if self.audio.is_muted() { "muted" } else { "unmuted" }));
+            }
+        }
+
+        // Procesar eventos del jugador (movimiento y rotación), desde la plataforma o desde la grabación
+        let now = platform.now();
+        let live_dt = now.saturating_sub(self.last_tick).as_secs_f32().min(0.1);
+        self.last_tick = now;
+        // La música baja mientras la ventana no tiene el foco
+        self.audio.set_music_ducked(!platform.is_focused());
+        self.audio.update(live_dt);
+        let mut live_input = frame_input.input;
+        self.mouse.apply(&mut live_input);
+        let (dt, input) = self.next_tick(platform, live_dt, live_input);
+        if let Some(demo) = self.demo.as_mut() {
+            demo.push(dt, input);
+        }
+        self.simulate(&input, dt);
+
+        self.render();
+
+        // Calcular FPS e imprimir el texto
+        self.frame_count += 1;
+        let elapsed = now.saturating_sub(self.last_update);
+        if elapsed >= Duration::from_secs(1) {
+            platform.show_message(&format!("FPS: {:.1}", self.frame_count as f32 / elapsed.as_secs_f32()));
+            self.frame_count = 0;
+            self.last_update = now;
+        }
+
+        // Ampliar a la resolución de la ventana solo si se renderiza a menor escala
+        let start = Instant::now();
+        let presented = match self.screen.as_mut() {
+            Some(screen) => {
+                self.framebuffer.upscale_into(screen, self.filter);
+                screen
+            }
+            None => &mut self.framebuffer,
+        };
+        self.profiler.record(Phase::Conversion, start.elapsed());
+
+        // Captura de pantalla
+        if let Some(format) = actions.screenshot {
+            match save_screenshot(presented, Path::new("screenshots"), format) {
+                Ok(path) => {
+                    self.audio.play(SoundEffect::Click);
+                    platform.show_message(&format!("Screenshot saved to {}", path.display()));
+                }
+                Err(e) => platform.show_message(&format!("Error saving screenshot: {}", e)),
+            }
+        }
+
+        // Grabación: la misma acción inicia y detiene
+        if actions.toggle_recording {
+            self.audio.play(SoundEffect::Click);
+            if self.recorder.is_recording() {
+                report_recording(platform, self.recorder.stop());
+            } else {
+                match self.recorder.start(Path::new("recordings")) {
+                    Ok(path) => platform.show_message(&format!("Recording to {}", path.display())),
+                    Err(e) => platform.show_message(&format!("Error starting recording: {}", e)),
+                }
+            }
+        }
+        if self.recorder.is_recording() {
+            report_recording(platform, self.recorder.capture(presented));
+        }
+
+        // El gráfico se dibuja después de las capturas para que no aparezca en ellas
+        if self.profiler.visible {
+            let start = Instant::now();
+            self.profiler.draw_overlay(presented, self.font.as_ref());
+            self.profiler.record(Phase::Hud, start.elapsed());
+        }
+
+        let start = Instant::now();
+        platform.present(presented)?;
+        self.profiler.record(Phase::Present, start.elapsed());
+        platform.sleep(FRAME_SLEEP);
+        Ok(true)
+    }
+
+    // Tick de la grabación si hay una en curso; si no, la entrada en vivo
+    fn next_tick<P: Platform>(&mut self, platform: &mut P, live_dt: f32, live_input: InputState) -> (f32, InputState) {
+        match self.playback.as_mut().map(DemoPlayback::next_tick) {
+            Some(Some(tick)) => tick,
+            Some(None) => {
+                platform.show_message("Demo playback finished");
+                self.playback = None;
+                (live_dt, live_input)
+            }
+            None => (live_dt, live_input),
+        }
+    }
+
+    // Mueve al jugador y dispara los sonidos de pasos, choques y meta
+    fn simulate(&mut self, input: &InputState, dt: f32) {
+        let events = step(input, dt, &mut self.player, &self.maze, self.block_size);
+
+        self.distance_since_step += events.distance;
+        if self.distance_since_step >= FOOTSTEP_DISTANCE {
+            self.distance_since_step -= FOOTSTEP_DISTANCE;
+            self.audio.play(SoundEffect::Footstep);
+        }
+        if events.bumped_wall && !self.was_bumping {
+            self.audio.play(SoundEffect::WallBump);
+        }
+        self.was_bumping = events.bumped_wall;
+        if events.reached_goal {
+            self.audio.play(SoundEffect::GoalChime);
+        }
+        self.audio.update_emitters(&self.player, &self.maze, self.block_size);
+    }
+
+    fn render(&mut self) {
+        let framebuffer = &mut self.framebuffer;
+        framebuffer.clear(); // Limpiar el framebuffer al principio
+        framebuffer.set_current_color(Color::new(50, 50, 100));
+        match self.mode {
+            // En 2D el mapa y sus rayos cuentan como paredes
+            ViewMode::Map2D => {
+                let start = Instant::now();
+                render2d(framebuffer, &self.maze, &self.player);
+                self.profiler.record(Phase::WallDraw, start.elapsed());
+            }
+            ViewMode::View3D => {
+                let timings =
+                    render3d_threaded(framebuffer, &self.maze, &self.player, &self.texture, self.render_threads);
+                self.profiler.record_render(timings);
+            }
+        }
+        framebuffer.end_frame();
+    }
+
+    fn export_profile<P: Platform>(&mut self, platform: &mut P, path: &Path) {
+        match self.profiler.write_csv(path) {
+            Ok(()) => platform.show_message(&format!("Profile written to {}", path.display())),
+            Err(e) => platform.show_message(&format!("Error writing profile: {}", e)),
+        }
+    }
+}
+
+// Música y sonidos ambientales de un nivel: la lista `.playlist` junto al mapa, o la pista por
+// defecto si no existe
+fn start_level_audio(audio: &mut Audio, assets: &Assets, map: &Path, maze: &Map, block_size: usize) {
+    let playlist = assets.resolve(map).ok().and_then(|path| level_playlist(&path));
+    match playlist.map_or_else(|| assets.resolve("assets/background_music.mp3").map(|track| vec![track]), Ok) {
+        Ok(tracks) => audio.play_playlist(tracks),
+        Err(e) => eprintln!("Warning: {}; playing without music", e),
+    }
+
+    // Sonidos ambientales ubicados en el laberinto
+    for (effect, pos) in map_emitters(maze, block_size) {
+        audio.add_emitter(effect, pos);
+    }
+}
+
+fn report_recording<P: Platform>(platform: &mut P, result: Result<Option<RecordingSummary>, Box<dyn Error>>) {
+    match result {
+        Ok(Some(summary)) => {
+            let mut message = format!("Recorded {} frames to {}", summary.frames, summary.output.display());
+            if summary.dropped > 0 {
+                message += &format!(" ({} dropped, the encoder fell behind)", summary.dropped);
+            }
+            platform.show_message(&message);
+        }
+        Ok(None) => {}
+        Err(e) => platform.show_message(&format!("Error recording: {}", e)),
+    }
+}
@@ -0,0 +1,228 @@
+use std::error::Error;
+use std::path::PathBuf;
+use nalgebra_glm::Vec2;
+use crate::assets::Assets;
+use crate::capture::save_frame;
+use crate::config::Config;
+use crate::demo::Demo;
+use crate::framebuffer::FrameBuffer;
+use crate::game::WALL_TEXTURE;
+use crate::player::Player;
+use crate::render::{render2d, render3d};
+
+// Opciones de `--render-frame`: renderiza un solo cuadro sin abrir ventana y lo guarda en disco
+pub struct RenderFrameOptions {
+    pub output: PathBuf,
+    // Se buscan en `asset_roots`, como en el juego
+    pub map: PathBuf,
+    pub texture: PathBuf,
+    pub asset_roots: Vec<PathBuf>,
+    pub pos: Vec2,
+    pub angle: f32,
+    // En grados, como `--fov` en el modo normal
+    pub fov: Option<f32>,
+    pub width: usize,
+    pub height: usize,
+    pub mode_2d: bool,
+}
+
+impl RenderFrameOptions {
+    // Devuelve None si los argumentos no piden `--render-frame`. El mapa y las rutas de recursos
+    // por defecto son los de la configuración.
+    pub fn from_args(args: &[String], config: &Config) -> Result<Option<RenderFrameOptions>, String> {
+        let Some(index) = args.iter().position(|arg| arg == "--render-frame") else {
+            return Ok(None);
+        };
+        let output = args
+            .get(index + 1)
+            .filter(|value| !value.starts_with("--"))
+            .ok_or("--render-frame needs an output path")?;
+
+        let mut options = RenderFrameOptions {
+            output: PathBuf::from(output),
+            map: config.paths.map.clone(),
+            texture: PathBuf::from(WALL_TEXTURE),
+            asset_roots: config.asset_roots(),
+            pos: Vec2::new(250.0, 150.0),
+            angle: std::f32::consts::PI / 3.0,
+            fov: None,
+            width: 1000,
+            height: 900,
+            mode_2d: false,
+        };
+
+        let mut rest = args[index + 2..].iter();
+        while let Some(flag) = rest.next() {
+            let mut value = || rest.next().ok_or(format!("{} needs a value", flag));
+            match flag.as_str() {
+                "--map" => options.map = PathBuf::from(value()?),
+                // Ya leída al cargar la configuración
+                "--config" => {
+                    value()?;
+                }
+                "--texture" => options.texture = PathBuf::from(value()?),
+                "--pos" => {
+                    let (x, y) = parse_pair(value()?, ',')?;
+                    options.pos = Vec2::new(x, y);
+                }
+                "--angle" => options.angle = parse_number(value()?)?,
+                "--fov" => {
+                    let fov = parse_number(value()?)?;
+                    if !(fov > 0.0 && fov < 180.0) {
+                        return Err(format!("--fov must be between 0 and 180 degrees, got {}", fov));
+                    }
+                    options.fov = Some(fov);
+                }
+                "--size" => {
+                    let (width, height) = parse_pair(value()?, 'x')?;
+                    if width < 1.0 || height < 1.0 {
+                        return Err("--size must be at least 1x1".into());
+                    }
+                    options.width = width as usize;
+                    options.height = height as usize;
+                }
+                "--mode" => match value()?.to_ascii_lowercase().as_str() {
+                    "2d" => options.mode_2d = true,
+                    "3d" => options.mode_2d = false,
+                    other => return Err(format!("unknown mode '{}', expected 2d or 3d", other)),
+                },
+                other => return Err(format!("unknown option for --render-frame: {}", other)),
+            }
+        }
+
+        Ok(Some(options))
+    }
+}
+
+pub fn render_frame(options: &RenderFrameOptions) -> Result<(), Box<dyn Error>> {
+    // Sin reemplazos: en modo sin ventana un recurso que falta es un error
+    let mut assets = Assets::new(options.asset_roots.clone());
+    let maze = assets.load_map(&options.map)?;
+    let texture = assets.load_texture(&options.texture)?;
+    let (maze, texture) = (assets.map(maze), assets.texture(texture));
+
+    let mut player = Player::new(options.pos, options.angle);
+    if let Some(fov) = options.fov {
+        player.fov = fov.to_radians();
+    }
+
+    let mut framebuffer = FrameBuffer::new(options.width, options.height);
+    framebuffer.clear();
+    if options.mode_2d {
+        render2d(&mut framebuffer, maze, &player);
+    } else {
+        render3d(&mut framebuffer, maze, &player, texture);
+    }
+
+    save_frame(&framebuffer, &options.output)
+}
+
+// `--replay <archivo> [--map <laberinto>]`: repite una grabación sin ventana e imprime la pose final.
+// Sin `--map` se usa el laberinto guardado en la grabación y, si no lo tiene, el de la configuración.
+pub fn replay_demo(path: &str, map: Option<&str>, config: &Config) -> Result<(), Box<dyn Error>> {
+    let demo = Demo::load(path)?;
+    let map = map.map(PathBuf::from).or_else(|| demo.map.clone()).unwrap_or_else(|| config.paths.map.clone());
+    let mut assets = Assets::new(config.asset_roots());
+    let maze = assets.load_map(&map)?;
+    let player = demo.replay(assets.map(maze));
+    println!(
+        "Replayed {} ticks (seed {}): pos = ({}, {}), angle = {}",
+        demo.ticks.len(),
+        demo.seed,
+        player.pos.x,
+        player.pos.y,
+        player.a
+    );
+    Ok(())
+}
+
+// Valor que sigue a `flag` en los argumentos, si está presente
+pub fn arg_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    let index = args.iter().position(|arg| arg == flag)?;
+    args.get(index + 1).map(String::as_str)
+}
+
+fn parse_number(value: &str) -> Result<f32, String> {
+    value.parse().map_err(|_| format!("invalid number: {}", value))
+}
+
+fn parse_pair(value: &str, separator: char) -> Result<(f32, f32), String> {
+    let (a, b) = value
+        .split_once(separator)
+        .ok_or(format!("expected two values separated by '{}': {}", separator, value))?;
+    Ok((parse_number(a)?, parse_number(b)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    // Mapas de prueba primero y después la raíz del proyecto, donde están las texturas
+    fn fixture_config() -> Config {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let mut config = Config::default();
+        config.paths.map = PathBuf::from("corridor.txt");
+        config.paths.asset_root = root.join("tests/fixtures");
+        config.paths.search_paths = vec![root.to_path_buf()];
+        config
+    }
+
+    fn args(list: &[&str]) -> Vec<String> {
+        std::iter::once("ray_casting").chain(list.iter().copied()).map(String::from).collect()
+    }
+
+    #[test]
+    fn render_frame_finds_the_configured_map_in_the_asset_roots() {
+        let config = fixture_config();
+        let output = std::env::temp_dir().join(format!("ray_casting_render_frame_{}.bmp", std::process::id()));
+        let options = RenderFrameOptions::from_args(
+            &args(&["--render-frame", &output.to_string_lossy(), "--size", "40x30", "--config", "unused.toml"]),
+            &config,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(options.map, PathBuf::from("corridor.txt"));
+        assert_eq!(options.texture, PathBuf::from(WALL_TEXTURE));
+        assert_eq!(options.asset_roots, config.asset_roots());
+
+        render_frame(&options).unwrap();
+        let frame = FrameBuffer::from_bmp(&output).unwrap();
+        std::fs::remove_file(&output).unwrap();
+        assert_eq!((frame.width, frame.height), (40, 30));
+    }
+
+    #[test]
+    fn render_frame_fov_is_in_degrees() {
+        let config = fixture_config();
+        let render = |fov: Option<&str>| {
+            let output = std::env::temp_dir().join(format!("ray_casting_fov_{}_{:?}.bmp", std::process::id(), fov));
+            let mut list = vec!["--render-frame", output.to_str().unwrap(), "--size", "40x30"];
+            list.extend(fov.map(|fov| ["--fov", fov]).into_iter().flatten());
+            render_frame(&RenderFrameOptions::from_args(&args(&list), &config).unwrap().unwrap()).unwrap();
+            let frame = FrameBuffer::from_bmp(&output).unwrap();
+            std::fs::remove_file(&output).unwrap();
+            frame.buffer
+        };
+        // El jugador empieza con 60 grados, así que pedirlos explícitamente no cambia el cuadro
+        assert_eq!(render(Some("60")), render(None));
+        assert_ne!(render(Some("90")), render(None));
+
+        for fov in ["0", "180", "NaN", "-5"] {
+            let options = RenderFrameOptions::from_args(&args(&["--render-frame", "out.bmp", "--fov", fov]), &config);
+            assert!(options.is_err(), "fov = {}", fov);
+        }
+    }
+
+    #[test]
+    fn replay_falls_back_to_the_configured_map() {
+        let mut config = fixture_config();
+        let demo = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/corridor_walk.demo");
+        let demo = demo.to_string_lossy();
+        assert!(replay_demo(&demo, None, &config).is_ok());
+
+        config.paths.map = PathBuf::from("missing.txt");
+        assert!(replay_demo(&demo, None, &config).is_err());
+        assert!(replay_demo(&demo, Some("rooms.txt"), &config).is_ok());
+    }
+}
@@ -0,0 +1,38 @@
+// Motor de ray casting: mapa, cámara, caster, render, framebuffer y entrada, sin depender de
+// una ventana. El binario (`main.rs`) solo agrega la ventana y el audio, y otras herramientas,
+// pruebas y benchmarks pueden usar el motor directamente. Sin la feature `audio` no se enlaza
+// rodio (ni ALSA) y el audio queda siempre en silencio.
+pub mod framebuffer;
+pub mod blit;
+pub mod color;
+pub mod bmp;
+pub mod maze;
+pub mod player;
+pub mod caster;
+pub mod render;
+pub mod capture;
+pub mod headless;
+pub mod recorder;
+pub mod demo;
+pub mod audio;
+pub mod positional;
+pub mod music;
+#[cfg(not(feature = "audio"))]
+mod no_audio;
+pub mod config;
+pub mod assets;
+pub mod platform;
+pub mod game;
+pub mod terminal;
+pub mod profiler;
+
+pub use assets::{AssetError, Assets, Handle};
+pub use caster::{cast_ray, Intersect};
+pub use color::Color;
+pub use framebuffer::FrameBuffer;
+pub use game::{Game, ViewMode};
+pub use maze::{load_maze, parse_maze};
+pub use platform::{Actions, FrameInput, Platform, ScriptedPlatform};
+pub use profiler::{Phase, Profiler};
+pub use player::{step, InputState, MouseFilter, MoveEvents, Player};
+pub use render::{block_size, render2d, render3d, render3d_threaded, RenderTimings};
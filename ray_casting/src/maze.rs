@@ -1,19 +1,35 @@
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
+use std::fs;
+
+// Ancho del mundo en unidades de juego: el laberinto se reparte en él sin importar la resolución
+pub const WORLD_WIDTH: usize = 1000;
+
+// Celdas por las que se camina y que los rayos atraviesan: vacías, el inicio 'p' y la meta 'g'
+pub fn is_open(cell: char) -> bool {
+    matches!(cell, ' ' | 'p' | 'g')
+}
 
 // Función para cargar el laberinto desde un archivo
 pub fn load_maze(file_path: &str) -> Result<Vec<Vec<char>>, Box<dyn std::error::Error>> {
-    let path = Path::new(file_path);
-    let file = File::open(&path)?;
-    let reader = io::BufReader::new(file);
+    let text = fs::read_to_string(file_path)?;
+    Ok(parse_maze(&text)?)
+}
+
+// Convierte el texto del mapa en filas de celdas
+pub fn parse_maze(text: &str) -> Result<Vec<Vec<char>>, String> {
     let mut maze = Vec::new();
     let mut row_length = None;
 
-    for line in reader.lines() {
-        let line = line?;
+    for (number, line) in text.lines().enumerate() {
         let row: Vec<char> = line.chars().collect();
-        
+
+        // Cada celda ocupa WORLD_WIDTH / columnas unidades; más columnas dejarían celdas de tamaño 0
+        if row.is_empty() {
+            return Err(format!("Empty row {} in maze file", number + 1));
+        }
+        if row.len() > WORLD_WIDTH {
+            return Err(format!("Row {} of the maze file is wider than {} columns", number + 1, WORLD_WIDTH));
+        }
+
         // Verificar que todas las filas tengan la misma longitud
         if let Some(length) = row_length {
             if row.len() != length {
@@ -22,9 +38,31 @@ pub fn load_maze(file_path: &str) -> Result<Vec<Vec<char>>, Box<dyn std::error::
         } else {
             row_length = Some(row.len());
         }
-        
+
         maze.push(row);
     }
 
+    if maze.is_empty() {
+        return Err("Empty maze file".into());
+    }
+
     Ok(maze)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_empty_and_too_wide_rows() {
+        assert_eq!(parse_maze("+-+\n| |\n+-+\n").unwrap().len(), 3);
+        assert!(parse_maze("").is_err());
+        assert!(parse_maze("+-+\n\n+-+\n").unwrap_err().contains("Empty row 2"));
+        assert!(parse_maze("\n").unwrap_err().contains("Empty row 1"));
+
+        let widest = "+".repeat(WORLD_WIDTH);
+        assert_eq!(parse_maze(&widest).unwrap()[0].len(), WORLD_WIDTH);
+        let too_wide = "+".repeat(WORLD_WIDTH + 1);
+        assert!(parse_maze(&too_wide).unwrap_err().contains("wider than 1000 columns"));
+    }
+}
@@ -0,0 +1,187 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+#[cfg(feature = "audio")]
+use rodio::{Decoder, OutputStreamHandle, Sink};
+#[cfg(not(feature = "audio"))]
+use crate::no_audio::{Decoder, OutputStreamHandle, Sink};
+
+// Duración del fundido cruzado al cambiar de lista
+const CROSSFADE_SECONDS: f32 = 2.0;
+// Fracción del volumen que queda mientras la música está atenuada
+const DUCK_LEVEL: f32 = 0.3;
+// Velocidad con la que se baja o sube el volumen al atenuar, en fracciones por segundo
+const DUCK_SPEED: f32 = 3.0;
+
+// Lee la lista de pistas de un nivel: un archivo `<mapa>.playlist` con una ruta por línea,
+// relativa al propio archivo. Las líneas vacías y las que empiezan con '#' se ignoran.
+pub fn level_playlist(map_path: &Path) -> Option<Vec<PathBuf>> {
+    let playlist_path = map_path.with_extension("playlist");
+    let contents = std::fs::read_to_string(&playlist_path).ok()?;
+    let base = playlist_path.parent().unwrap_or(Path::new(""));
+    let tracks: Vec<PathBuf> = contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| base.join(line))
+        .collect();
+    if tracks.is_empty() { None } else { Some(tracks) }
+}
+
+// Lista en reproducción: encola la pista siguiente antes de que termine la actual para que
+// el cambio sea continuo, y vuelve al principio al llegar al final. Sin dispositivo no hay sink,
+// pero el fundido avanza igual.
+struct Playlist {
+    sink: Option<Sink>,
+    // Lista pedida, para reconocerla aunque se hayan descartado pistas rotas
+    requested: Vec<PathBuf>,
+    tracks: Vec<PathBuf>,
+    next_track: usize,
+    // Ganancia del fundido, de 0 a 1
+    fade: f32,
+    // Último volumen aplicado: fundido por volumen de la categoría y atenuación
+    volume: f32,
+}
+
+impl Playlist {
+    fn start(handle: Option<&OutputStreamHandle>, tracks: Vec<PathBuf>) -> Result<Playlist, Box<dyn Error>> {
+        let mut playlist = Playlist {
+            sink: handle.map(Sink::try_new).transpose()?,
+            requested: tracks.clone(),
+            tracks,
+            next_track: 0,
+            fade: 0.0,
+            volume: 0.0,
+        };
+        playlist.set_volume(0.0);
+        playlist.enqueue_next()?;
+        Ok(playlist)
+    }
+
+    fn set_volume(&mut self, volume: f32) {
+        self.volume = volume;
+        if let Some(sink) = &self.sink {
+            sink.set_volume(volume);
+        }
+    }
+
+    // Agrega pistas a la cola hasta tener una esperando detrás de la actual.
+    // Las que no se puedan abrir se avisan una vez y se quitan de la lista.
+    fn enqueue_next(&mut self) -> Result<(), Box<dyn Error>> {
+        let Some(sink) = &self.sink else {
+            return Ok(());
+        };
+        while sink.len() < 2 && !self.tracks.is_empty() {
+            let index = self.next_track % self.tracks.len();
+            match open_track(&self.tracks[index]) {
+                Ok(source) => {
+                    sink.append(source);
+                    self.next_track = (index + 1) % self.tracks.len();
+                }
+                Err(e) => {
+                    eprintln!("Warning: could not play music '{}': {}", self.tracks[index].display(), e);
+                    self.tracks.remove(index);
+                    self.next_track = index;
+                }
+            }
+        }
+        if self.tracks.is_empty() && sink.empty() {
+            return Err("no playable music tracks".into());
+        }
+        Ok(())
+    }
+}
+
+fn open_track(path: &Path) -> Result<Decoder<BufReader<File>>, Box<dyn Error>> {
+    Ok(Decoder::new(BufReader::new(File::open(path)?))?)
+}
+
+// Música de fondo: listas en bucle, fundido cruzado al cambiar de nivel y atenuación, p. ej. durante
+// un menú. Sin dispositivo (`handle` en None) no suena nada, pero los volúmenes evolucionan igual.
+pub struct MusicController {
+    handle: Option<OutputStreamHandle>,
+    current: Option<Playlist>,
+    // Listas anteriores que se desvanecen durante el fundido cruzado
+    fading_out: Vec<Playlist>,
+    ducked: bool,
+    duck_gain: f32,
+}
+
+impl MusicController {
+    pub fn new(handle: Option<OutputStreamHandle>) -> MusicController {
+        MusicController {
+            handle,
+            current: None,
+            fading_out: Vec::new(),
+            ducked: false,
+            duck_gain: 1.0,
+        }
+    }
+
+    // Cambia a otra lista con un fundido cruzado desde la que esté sonando
+    pub fn play_playlist(&mut self, tracks: Vec<PathBuf>) -> Result<(), Box<dyn Error>> {
+        if tracks.is_empty() {
+            return Err("empty playlist".into());
+        }
+        if self.current.as_ref().is_some_and(|playlist| playlist.requested == tracks) {
+            return Ok(());
+        }
+        let playlist = Playlist::start(self.handle.as_ref(), tracks)?;
+        if let Some(previous) = self.current.replace(playlist) {
+            self.fading_out.push(previous);
+        }
+        Ok(())
+    }
+
+    // Atenúa la música hasta DUCK_LEVEL, o la devuelve a su volumen, en unos 0.2 segundos
+    pub fn set_ducked(&mut self, ducked: bool) {
+        self.ducked = ducked;
+    }
+
+    // Avanza fundidos y atenuación y mantiene la cola llena; llamar una vez por cuadro
+    pub fn update(&mut self, dt: f32, volume: f32) {
+        let fade_step = dt / CROSSFADE_SECONDS;
+        let duck_target = if self.ducked { DUCK_LEVEL } else { 1.0 };
+        let duck_step = DUCK_SPEED * dt;
+        self.duck_gain += (duck_target - self.duck_gain).clamp(-duck_step, duck_step);
+        let gain = volume * self.duck_gain;
+
+        if let Some(playlist) = self.current.as_mut() {
+            playlist.fade = (playlist.fade + fade_step).min(1.0);
+            playlist.set_volume(playlist.fade * gain);
+            if playlist.enqueue_next().is_err() {
+                self.current = None;
+            }
+        }
+
+        self.fading_out.retain_mut(|playlist| {
+            playlist.fade -= fade_step;
+            playlist.set_volume(playlist.fade.max(0.0) * gain);
+            playlist.fade > 0.0
+        });
+    }
+
+    // Volumen de cada lista que suena: primero la actual, después las que se desvanecen
+    pub fn volumes(&self) -> Vec<f32> {
+        self.current.iter().chain(&self.fading_out).map(|playlist| playlist.volume).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn level_playlist_resolves_tracks_next_to_the_map() {
+        let dir = std::env::temp_dir().join(format!("ray_casting_playlist_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("level.playlist"), "# comentario\n\nintro.mp3\n  music/loop.ogg  \n").unwrap();
+
+        let tracks = level_playlist(&dir.join("level.txt")).unwrap();
+        assert_eq!(tracks, vec![dir.join("intro.mp3"), dir.join("music/loop.ogg")]);
+        assert!(level_playlist(&dir.join("missing.txt")).is_none());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
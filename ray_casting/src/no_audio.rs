@@ -0,0 +1,79 @@
+// Reemplazo de rodio al compilar sin la feature `audio` (p. ej. en CI sin ALSA). Tiene los mismos
+// nombres que usa el motor, pero no hay dispositivo: `OutputStream::try_default` y `Decoder::new`
+// siempre fallan, así que `Audio` funciona como backend nulo y el resto nunca llega a usarse.
+use std::fmt;
+use std::marker::PhantomData;
+
+#[derive(Debug)]
+pub struct NoAudio;
+
+impl fmt::Display for NoAudio {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "built without audio support")
+    }
+}
+
+impl std::error::Error for NoAudio {}
+
+pub struct OutputStream;
+
+impl OutputStream {
+    pub fn try_default() -> Result<(OutputStream, OutputStreamHandle), NoAudio> {
+        Err(NoAudio)
+    }
+}
+
+#[derive(Clone)]
+pub struct OutputStreamHandle;
+
+pub struct Sink;
+
+impl Sink {
+    pub fn try_new(_handle: &OutputStreamHandle) -> Result<Sink, NoAudio> {
+        Err(NoAudio)
+    }
+
+    pub fn append<S>(&self, _source: S) {}
+
+    pub fn set_volume(&self, _volume: f32) {}
+
+    pub fn play(&self) {}
+
+    pub fn stop(&self) {}
+
+    pub fn empty(&self) -> bool {
+        true
+    }
+
+    pub fn len(&self) -> usize {
+        0
+    }
+}
+
+pub struct SamplesBuffer;
+
+impl SamplesBuffer {
+    pub fn new(_channels: u16, _sample_rate: u32, _samples: Vec<f32>) -> SamplesBuffer {
+        SamplesBuffer
+    }
+}
+
+pub struct Decoder<R>(PhantomData<R>);
+
+impl<R> Decoder<R> {
+    pub fn new(_data: R) -> Result<Decoder<R>, NoAudio> {
+        Err(NoAudio)
+    }
+
+    pub fn channels(&self) -> u16 {
+        0
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        0
+    }
+
+    pub fn convert_samples(self) -> std::iter::Empty<f32> {
+        std::iter::empty()
+    }
+}
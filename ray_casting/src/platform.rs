@@ -0,0 +1,119 @@
+use std::collections::VecDeque;
+use std::error::Error;
+use std::time::Duration;
+use crate::capture::ImageFormat;
+use crate::framebuffer::FrameBuffer;
+use crate::player::InputState;
+
+// Órdenes de un cuadro que no son movimiento: teclas de una sola pulsación
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct Actions {
+    pub quit: bool,
+    pub toggle_view: bool,
+    pub toggle_mute: bool,
+    pub toggle_recording: bool,
+    pub screenshot: Option<ImageFormat>,
+    pub toggle_profiler: bool,
+    // Guarda las estadísticas del perfilador en CSV
+    pub export_profile: bool,
+}
+
+// Todo lo que el bucle del juego lee de la plataforma en un cuadro
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct FrameInput {
+    pub input: InputState,
+    pub actions: Actions,
+}
+
+// Lo que el bucle del juego necesita de la ventana: mostrar cuadros, leer la entrada y medir el tiempo.
+// Permite correr el mismo bucle con minifb, en la terminal o sin ventana en pruebas y benchmarks.
+pub trait Platform {
+    // false cuando el usuario cerró la ventana o se terminó el guion
+    fn is_open(&self) -> bool;
+
+    fn poll_input(&mut self) -> FrameInput;
+
+    // Muestra un cuadro del tamaño de la ventana
+    fn present(&mut self, frame: &FrameBuffer) -> Result<(), Box<dyn Error>>;
+
+    // Tiempo transcurrido desde que se creó la plataforma
+    fn now(&self) -> Duration;
+
+    // Espera entre cuadros
+    fn sleep(&mut self, duration: Duration);
+
+    // false mientras la ventana está en segundo plano; la música se atenúa hasta recuperarlo
+    fn is_focused(&mut self) -> bool {
+        true
+    }
+
+    // Mensajes para el jugador (FPS, audio, capturas); por defecto a la salida estándar
+    fn show_message(&mut self, message: &str) {
+        println!("{}", message);
+    }
+}
+
+// Plataforma sin ventana que reproduce una lista fija de entradas, una por cuadro, con un reloj
+// virtual que solo avanza en `sleep`. El resultado no depende de la velocidad de la máquina.
+pub struct ScriptedPlatform {
+    script: VecDeque<FrameInput>,
+    clock: Duration,
+    pub presented_frames: usize,
+    pub last_frame: Option<FrameBuffer>,
+    pub messages: Vec<String>,
+    pub focused: bool,
+}
+
+impl ScriptedPlatform {
+    pub fn new<I: IntoIterator<Item = FrameInput>>(script: I) -> ScriptedPlatform {
+        ScriptedPlatform {
+            script: script.into_iter().collect(),
+            clock: Duration::ZERO,
+            presented_frames: 0,
+            last_frame: None,
+            messages: Vec::new(),
+            focused: true,
+        }
+    }
+
+    // Guion que repite la misma entrada durante `frames` cuadros
+    pub fn repeat(input: FrameInput, frames: usize) -> ScriptedPlatform {
+        ScriptedPlatform::new(std::iter::repeat_n(input, frames))
+    }
+}
+
+impl Platform for ScriptedPlatform {
+    fn is_open(&self) -> bool {
+        !self.script.is_empty()
+    }
+
+    fn is_focused(&mut self) -> bool {
+        self.focused
+    }
+
+    fn poll_input(&mut self) -> FrameInput {
+        self.script.pop_front().unwrap_or_default()
+    }
+
+    fn present(&mut self, frame: &FrameBuffer) -> Result<(), Box<dyn Error>> {
+        let copy = self.last_frame.get_or_insert_with(|| FrameBuffer::new(frame.width, frame.height));
+        if (copy.width, copy.height) != (frame.width, frame.height) {
+            *copy = FrameBuffer::new(frame.width, frame.height);
+        }
+        copy.buffer.copy_from_slice(&frame.buffer);
+        self.presented_frames += 1;
+        Ok(())
+    }
+
+    fn now(&self) -> Duration {
+        self.clock
+    }
+
+    fn sleep(&mut self, duration: Duration) {
+        self.clock += duration;
+    }
+
+    fn show_message(&mut self, message: &str) {
+        self.messages.push(message.to_string());
+    }
+}
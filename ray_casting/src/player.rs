@@ -1,54 +1,253 @@
 use std::f32::consts::PI;
 use nalgebra_glm::Vec2;
-use minifb::{Window, Key};
+use crate::maze::is_open;
 
 pub struct Player {
     pub pos: Vec2,
     pub a: f32,
     pub fov: f32,
     pub mouse_sensitivity: f32,
-    pub last_mouse_x: f32,
+    // Cabeceo por píxel de mouse, en altos de la vista; aparte del giro porque usa otra unidad
+    pub mouse_pitch_sensitivity: f32,
+    // Cabeceo como desplazamiento del horizonte, en fracciones del alto de la vista;
+    // positivo mira hacia arriba. Se limita a ±MAX_PITCH.
+    pub pitch: f32,
+    // Altura de los ojos en fracciones del alto de una pared: 0.5 es la mitad. Más baja al
+    // agacharse, más alta al saltar.
+    pub eye_height: f32,
 }
 
-pub fn process_event(window: &Window, player: &mut Player) {
-    const SPEED: f32 = 5.0;
-    const ROTATION_SPEED: f32 = PI / 10.0;
+impl Player {
+    pub fn new(pos: Vec2, a: f32) -> Player {
+        Player {
+            pos,
+            a,
+            fov: PI / 3.0,
+            mouse_sensitivity: 0.005,
+            mouse_pitch_sensitivity: 0.001,
+            pitch: 0.0,
+            eye_height: 0.5,
+        }
+    }
+}
+
+// Acciones de un tick, independientes de la ventana para poder grabarlas y repetirlas
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct InputState {
+    pub forward: bool,
+    pub backward: bool,
+    pub turn_left: bool,
+    pub turn_right: bool,
+    pub look_up: bool,
+    pub look_down: bool,
+    // Desplazamiento horizontal del mouse desde el tick anterior, en píxeles
+    pub mouse_dx: f32,
+    // Desplazamiento vertical del mouse, en píxeles; positivo hacia abajo
+    pub mouse_dy: f32,
+}
+
+// Velocidades por segundo: 5 unidades y PI/10 por cuadro a 60 cuadros por segundo
+const SPEED: f32 = 300.0;
+const ROTATION_SPEED: f32 = 6.0 * PI;
+// Cabeceo con las teclas, en altos de la vista por segundo
+const PITCH_SPEED: f32 = 1.0;
+// El horizonte puede llegar hasta el borde superior o inferior de la vista
+pub const MAX_PITCH: f32 = 0.5;
 
+// Aplica un tick de entrada; el resultado depende solo de `input`, `dt` y el estado del jugador
+pub fn process_event(input: &InputState, dt: f32, player: &mut Player) {
     // Rotación con las teclas izquierda y derecha
-    if window.is_key_down(Key::Left) {
-        player.a -= ROTATION_SPEED;
+    if input.turn_left {
+        player.a -= ROTATION_SPEED * dt;
     }
-    if window.is_key_down(Key::Right) {
-        player.a += ROTATION_SPEED;
+    if input.turn_right {
+        player.a += ROTATION_SPEED * dt;
     }
 
     // Movimiento hacia adelante y hacia atrás
-    let (dx, dy) = (SPEED * player.a.cos(), SPEED * player.a.sin());
+    let (dx, dy) = (SPEED * dt * player.a.cos(), SPEED * dt * player.a.sin());
 
-    if window.is_key_down(Key::Up) {
+    if input.forward {
         player.pos.x += dx;
         player.pos.y += dy;
     }
-    if window.is_key_down(Key::Down) {
+    if input.backward {
         player.pos.x -= dx;
         player.pos.y -= dy;
     }
 
     // Rotación con el mouse
-    if let Some(mouse_pos) = window.get_mouse_pos(minifb::MouseMode::Discard) {
-        let mouse_x = mouse_pos.0 as f32;
-        let mouse_sensitivity = player.mouse_sensitivity;
+    if input.mouse_dx != 0.0 {
+        // Ajustar el ángulo basado en el cambio del mouse
+        player.a -= input.mouse_dx * player.mouse_sensitivity;
+    }
+    // Mantener el ángulo en el rango [0, 2π), también cuando es negativo
+    player.a = player.a.rem_euclid(2.0 * PI);
+    if player.a >= 2.0 * PI {
+        // rem_euclid puede redondear a 2π con ángulos negativos muy pequeños
+        player.a = 0.0;
+    }
+
+    // Cabeceo con las teclas y con el movimiento vertical del mouse
+    if input.look_up {
+        player.pitch += PITCH_SPEED * dt;
+    }
+    if input.look_down {
+        player.pitch -= PITCH_SPEED * dt;
+    }
+    player.pitch -= input.mouse_dy * player.mouse_pitch_sensitivity;
+    player.pitch = player.pitch.clamp(-MAX_PITCH, MAX_PITCH);
+}
 
-        // Calcular el cambio en la posición del mouse
-        let delta_x = mouse_x - player.last_mouse_x;
+// Suavizado e inversión del movimiento del mouse antes de convertirlo en rotación.
+// Se aplica a la entrada en vivo, así que las grabaciones guardan el movimiento ya filtrado.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct MouseFilter {
+    // Fracción del movimiento anterior que se conserva en cada tick: 0 = sin suavizado
+    pub smoothing: f32,
+    pub invert_y: bool,
+    last: (f32, f32),
+}
 
-        // Ajustar el ángulo basado en el cambio del mouse
-        player.a -= delta_x * mouse_sensitivity;
+impl MouseFilter {
+    pub fn new(smoothing: f32, invert_y: bool) -> MouseFilter {
+        MouseFilter { smoothing: smoothing.clamp(0.0, 0.99), invert_y, last: (0.0, 0.0) }
+    }
+
+    pub fn apply(&mut self, input: &mut InputState) {
+        let dy = if self.invert_y { -input.mouse_dy } else { input.mouse_dy };
+        let blend = |last: f32, raw: f32| last * self.smoothing + raw * (1.0 - self.smoothing);
+        let mut smoothed = (blend(self.last.0, input.mouse_dx), blend(self.last.1, dy));
+        // Cortar la cola del suavizado para que la vista se detenga del todo
+        if smoothed.0.abs() < 0.01 {
+            smoothed.0 = 0.0;
+        }
+        if smoothed.1.abs() < 0.01 {
+            smoothed.1 = 0.0;
+        }
+        self.last = smoothed;
+        input.mouse_dx = smoothed.0;
+        input.mouse_dy = smoothed.1;
+    }
+}
+
+// Lo que ocurrió en un tick de movimiento, para disparar sonidos y otros efectos
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct MoveEvents {
+    pub distance: f32,
+    pub bumped_wall: bool,
+    pub reached_goal: bool,
+}
+
+// Aplica la entrada y resuelve las colisiones con las paredes del laberinto.
+// Cada eje se prueba por separado para que el jugador se deslice a lo largo de la pared.
+pub fn step(input: &InputState, dt: f32, player: &mut Player, maze: &[Vec<char>], block_size: usize) -> MoveEvents {
+    let previous = player.pos;
+    process_event(input, dt, player);
+    let target = player.pos;
+
+    // Si el jugador empezó dentro de una pared, se le deja salir sin colisiones
+    if !is_walkable(maze, block_size, previous) {
+        return MoveEvents {
+            distance: nalgebra_glm::distance(&previous, &target),
+            ..MoveEvents::default()
+        };
+    }
+
+    let mut pos = previous;
+    let mut bumped_wall = false;
+    if target.x != pos.x {
+        if is_walkable(maze, block_size, Vec2::new(target.x, pos.y)) {
+            pos.x = target.x;
+        } else {
+            bumped_wall = true;
+        }
+    }
+    if target.y != pos.y {
+        if is_walkable(maze, block_size, Vec2::new(pos.x, target.y)) {
+            pos.y = target.y;
+        } else {
+            bumped_wall = true;
+        }
+    }
+    player.pos = pos;
+
+    let goal = Some('g');
+    MoveEvents {
+        distance: nalgebra_glm::distance(&previous, &pos),
+        bumped_wall,
+        reached_goal: cell_at(maze, block_size, pos) == goal && cell_at(maze, block_size, previous) != goal,
+    }
+}
+
+pub fn cell_at(maze: &[Vec<char>], block_size: usize, pos: Vec2) -> Option<char> {
+    if pos.x < 0.0 || pos.y < 0.0 {
+        return None;
+    }
+    let i = pos.x as usize / block_size;
+    let j = pos.y as usize / block_size;
+    maze.get(j)?.get(i).copied()
+}
+
+pub fn is_walkable(maze: &[Vec<char>], block_size: usize, pos: Vec2) -> bool {
+    cell_at(maze, block_size, pos).is_some_and(is_open)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn turn(a: f32, input: InputState) -> f32 {
+        let mut player = Player::new(Vec2::new(0.0, 0.0), a);
+        process_event(&input, 1.0 / 60.0, &mut player);
+        player.a
+    }
+
+    #[test]
+    fn angle_stays_in_zero_to_two_pi() {
+        let left = InputState { turn_left: true, ..InputState::default() };
+        assert!((turn(0.0, left) - (2.0 * PI - ROTATION_SPEED / 60.0)).abs() < 1e-4);
+        let mouse = InputState { mouse_dx: 1000.0, ..InputState::default() };
+        let a = turn(0.1, mouse);
+        assert!((0.0..2.0 * PI).contains(&a), "a = {}", a);
+        assert_eq!(turn(-1e-9, InputState::default()), 0.0);
+    }
+
+    #[test]
+    fn mouse_pitch_has_its_own_sensitivity() {
+        let mut player = Player::new(Vec2::new(0.0, 0.0), 1.0);
+        player.mouse_sensitivity = 0.5;
+        player.mouse_pitch_sensitivity = 0.002;
+        let input = InputState { mouse_dy: -100.0, ..InputState::default() };
+        process_event(&input, 1.0 / 60.0, &mut player);
+        assert!((player.pitch - 0.2).abs() < 1e-6, "pitch = {}", player.pitch);
+        assert_eq!(player.a, 1.0);
+    }
+
+    #[test]
+    fn mouse_filter_smooths_and_inverts() {
+        let mut filter = MouseFilter::new(0.5, true);
+        let mut input = InputState { mouse_dx: 10.0, mouse_dy: 4.0, ..InputState::default() };
+        filter.apply(&mut input);
+        assert_eq!((input.mouse_dx, input.mouse_dy), (5.0, -2.0));
+
+        // Sin movimiento nuevo la vista frena hasta detenerse
+        let mut still = InputState::default();
+        for _ in 0..20 {
+            still = InputState::default();
+            filter.apply(&mut still);
+        }
+        assert_eq!((still.mouse_dx, still.mouse_dy), (0.0, 0.0));
+    }
 
-        // Actualizar la última posición del mouse
-        player.last_mouse_x = mouse_x;
+    #[test]
+    fn rays_pass_through_the_cells_the_player_can_walk_on() {
+        let maze: Vec<Vec<char>> = ["+--+", "|pg|", "+--+"].iter().map(|row| row.chars().collect()).collect();
+        let player = Player::new(Vec2::new(150.0, 150.0), 0.0);
 
-        // Opcional: Mantén el ángulo en el rango [0, 2π)
-        player.a = player.a % (2.0 * PI);
+        assert!(is_walkable(&maze, 100, Vec2::new(250.0, 150.0)));
+        let hit = crate::caster::cast_ray(&maze, &player, 0.0, 100);
+        assert_eq!(hit.impact, '|');
+        assert!((hit.distance - 150.0).abs() < 0.2, "distance = {}", hit.distance);
     }
 }
@@ -0,0 +1,234 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+#[cfg(feature = "audio")]
+use std::time::Duration;
+use nalgebra_glm::Vec2;
+#[cfg(feature = "audio")]
+use rodio::Source;
+use crate::audio::SoundEffect;
+use crate::player::{is_walkable, Player};
+
+// Distancia, en bloques, a partir de la cual un emisor deja de oírse
+const HEARING_BLOCKS: f32 = 6.0;
+// Ganancia que conserva un sonido detrás de una pared
+const OCCLUDED_GAIN: f32 = 0.45;
+
+// Parámetros que el juego actualiza cada cuadro y el hilo de audio lee muestra a muestra
+#[derive(Default)]
+pub struct EmitterParams {
+    left: AtomicU32,
+    right: AtomicU32,
+    muffle: AtomicU32,
+}
+
+impl EmitterParams {
+    fn store(value: &AtomicU32, v: f32) {
+        value.store(v.to_bits(), Ordering::Relaxed);
+    }
+
+    fn load(value: &AtomicU32) -> f32 {
+        f32::from_bits(value.load(Ordering::Relaxed))
+    }
+
+    pub fn set(&self, mix: ListenerMix) {
+        EmitterParams::store(&self.left, mix.left);
+        EmitterParams::store(&self.right, mix.right);
+        EmitterParams::store(&self.muffle, mix.muffle);
+    }
+}
+
+// Ganancias por oído y cuánto filtrar (0 = directo, 1 = totalmente tras una pared)
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct ListenerMix {
+    pub left: f32,
+    pub right: f32,
+    pub muffle: f32,
+}
+
+// Emisores definidos por el laberinto: la meta 'g' zumba y las paredes 'd' gotean
+pub fn map_emitters(maze: &[Vec<char>], block_size: usize) -> Vec<(SoundEffect, Vec2)> {
+    let center = |i: usize, j: usize| {
+        Vec2::new((i * block_size + block_size / 2) as f32, (j * block_size + block_size / 2) as f32)
+    };
+    let mut emitters = Vec::new();
+    for (j, row) in maze.iter().enumerate() {
+        for (i, &cell) in row.iter().enumerate() {
+            match cell {
+                'g' => emitters.push((SoundEffect::GoalHum, center(i, j))),
+                'd' => emitters.push((SoundEffect::Drip, center(i, j))),
+                _ => {}
+            }
+        }
+    }
+    emitters
+}
+
+// Calcula paneo, atenuación por distancia y oclusión de un emisor respecto al jugador
+pub fn listener_mix(player: &Player, emitter: Vec2, maze: &[Vec<char>], block_size: usize) -> ListenerMix {
+    let offset = emitter - player.pos;
+    let distance = offset.norm();
+    let max_distance = HEARING_BLOCKS * block_size as f32;
+    let attenuation = (1.0 - distance / max_distance).clamp(0.0, 1.0).powi(2);
+    if attenuation <= 0.0 {
+        return ListenerMix::default();
+    }
+
+    // Ángulo relativo a la vista: positivo hacia la derecha, porque el eje y crece hacia abajo
+    let direction = offset.y.atan2(offset.x);
+    let pan = if distance < 1.0 { 0.0 } else { (direction - player.a).sin() };
+    // Paneo de potencia constante
+    let angle = (pan + 1.0) * std::f32::consts::FRAC_PI_4;
+
+    let muffle = if is_occluded(player, emitter, maze, block_size) { 1.0 } else { 0.0 };
+    let gain = attenuation * if muffle > 0.0 { OCCLUDED_GAIN } else { 1.0 };
+
+    ListenerMix {
+        left: angle.cos() * gain,
+        right: angle.sin() * gain,
+        muffle,
+    }
+}
+
+// Prueba de oclusión sobre la cuadrícula: se marcha por el segmento hacia el emisor, igual que
+// `cast_ray`, y cualquier celda no transitable que no sea la del propio emisor lo tapa
+fn is_occluded(player: &Player, emitter: Vec2, maze: &[Vec<char>], block_size: usize) -> bool {
+    let emitter_cell = cell_index(emitter, block_size);
+    let offset = emitter - player.pos;
+    let distance = offset.norm();
+    let step = block_size as f32 / 8.0;
+    let mut d = step;
+    while d < distance {
+        let point = player.pos + offset * (d / distance);
+        if cell_index(point, block_size) == emitter_cell {
+            return false;
+        }
+        if !is_walkable(maze, block_size, point) {
+            return true;
+        }
+        d += step;
+    }
+    false
+}
+
+fn cell_index(pos: Vec2, block_size: usize) -> (usize, usize) {
+    (pos.x.max(0.0) as usize / block_size, pos.y.max(0.0) as usize / block_size)
+}
+
+// Fuente estéreo que repite un clip mono aplicando los parámetros compartidos.
+// Los cambios se suavizan para evitar chasquidos, y el filtro pasa bajos simula la pared.
+pub struct EmitterSource {
+    samples: Arc<Vec<f32>>,
+    // Solo lo lee rodio, a través de `Source`
+    #[cfg_attr(not(feature = "audio"), allow(dead_code))]
+    sample_rate: u32,
+    position: usize,
+    params: Arc<EmitterParams>,
+    current: ListenerMix,
+    low_pass: f32,
+    pending_right: Option<f32>,
+}
+
+impl EmitterSource {
+    pub fn new(samples: Arc<Vec<f32>>, sample_rate: u32, params: Arc<EmitterParams>) -> EmitterSource {
+        EmitterSource {
+            samples,
+            sample_rate,
+            position: 0,
+            params,
+            current: ListenerMix::default(),
+            low_pass: 0.0,
+            pending_right: None,
+        }
+    }
+}
+
+impl Iterator for EmitterSource {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if let Some(right) = self.pending_right.take() {
+            return Some(right);
+        }
+        if self.samples.is_empty() {
+            return None;
+        }
+
+        let dry = self.samples[self.position];
+        self.position = (self.position + 1) % self.samples.len();
+
+        const SMOOTHING: f32 = 0.002;
+        let target_left = EmitterParams::load(&self.params.left);
+        let target_right = EmitterParams::load(&self.params.right);
+        let target_muffle = EmitterParams::load(&self.params.muffle);
+        self.current.left += (target_left - self.current.left) * SMOOTHING;
+        self.current.right += (target_right - self.current.right) * SMOOTHING;
+        self.current.muffle += (target_muffle - self.current.muffle) * SMOOTHING;
+
+        self.low_pass += (dry - self.low_pass) * 0.08;
+        let sample = dry + (self.low_pass - dry) * self.current.muffle;
+
+        self.pending_right = Some(sample * self.current.right);
+        Some(sample * self.current.left)
+    }
+}
+
+#[cfg(feature = "audio")]
+impl Source for EmitterSource {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        2
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use crate::maze::load_maze;
+
+    fn rooms() -> Vec<Vec<char>> {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/rooms.txt");
+        load_maze(&path.to_string_lossy()).unwrap()
+    }
+
+    #[test]
+    fn pans_towards_the_side_of_the_emitter() {
+        let maze = rooms();
+        let player = Player::new(Vec2::new(150.0, 150.0), 0.0);
+
+        let ahead = listener_mix(&player, Vec2::new(350.0, 150.0), &maze, 100);
+        assert!((ahead.left - ahead.right).abs() < 1e-3, "{:?}", ahead);
+
+        // Con el eje y hacia abajo, +y queda a la derecha de quien mira hacia +x
+        let right = listener_mix(&player, Vec2::new(150.0, 350.0), &maze, 100);
+        assert!(right.right > right.left, "{:?}", right);
+        assert_eq!(right.muffle, 0.0);
+    }
+
+    #[test]
+    fn attenuates_with_distance_and_walls() {
+        let maze = rooms();
+        let player = Player::new(Vec2::new(150.0, 150.0), 0.0);
+
+        let near = listener_mix(&player, Vec2::new(250.0, 150.0), &maze, 100);
+        let far = listener_mix(&player, Vec2::new(550.0, 150.0), &maze, 100);
+        assert!(near.left > far.left);
+
+        let behind_wall = listener_mix(&player, Vec2::new(150.0, 550.0), &maze, 100);
+        assert_eq!(behind_wall.muffle, 1.0);
+
+        let out_of_range = listener_mix(&player, Vec2::new(850.0, 750.0), &maze, 100);
+        assert_eq!(out_of_range, ListenerMix::default());
+    }
+}
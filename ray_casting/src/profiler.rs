@@ -0,0 +1,293 @@
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::path::Path;
+use std::time::Duration;
+use rusttype::{point, Font, Scale};
+use crate::color::Color;
+use crate::framebuffer::FrameBuffer;
+use crate::render::RenderTimings;
+
+// Cuadros que se guardan para el gráfico y las estadísticas, unos 10 segundos a 60 FPS
+const HISTORY: usize = 600;
+// Cuadros que muestra el gráfico, uno por columna de píxeles
+const GRAPH_FRAMES: usize = 240;
+const GRAPH_HEIGHT: usize = 80;
+// Tiempo que llena el alto del gráfico
+const GRAPH_SCALE_MS: f32 = 33.3;
+const TEXT_SIZE: f32 = 13.0;
+const LINE_HEIGHT: usize = 15;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Phase {
+    Input,
+    RayCast,
+    WallDraw,
+    FloorDraw,
+    Hud,
+    // Ampliar el framebuffer al tamaño de la ventana
+    Conversion,
+    Present,
+}
+
+impl Phase {
+    pub const ALL: [Phase; 7] = [
+        Phase::Input,
+        Phase::RayCast,
+        Phase::WallDraw,
+        Phase::FloorDraw,
+        Phase::Hud,
+        Phase::Conversion,
+        Phase::Present,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Phase::Input => "input",
+            Phase::RayCast => "ray_cast",
+            Phase::WallDraw => "wall_draw",
+            Phase::FloorDraw => "floor_draw",
+            Phase::Hud => "hud",
+            Phase::Conversion => "conversion",
+            Phase::Present => "present",
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            Phase::Input => Color::new(200, 200, 200),
+            Phase::RayCast => Color::new(230, 80, 60),
+            Phase::WallDraw => Color::new(240, 180, 40),
+            Phase::FloorDraw => Color::new(80, 190, 90),
+            Phase::Hud => Color::new(70, 150, 230),
+            Phase::Conversion => Color::new(170, 90, 220),
+            Phase::Present => Color::new(60, 200, 200),
+        }
+    }
+}
+
+// Tiempos de un cuadro: el total de inicio a inicio, con la espera incluida, y el de cada fase
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct FrameSample {
+    pub total: Duration,
+    pub phases: [Duration; 7],
+}
+
+impl FrameSample {
+    pub fn phase(&self, phase: Phase) -> Duration {
+        self.phases[phase as usize]
+    }
+}
+
+// Mínimo, promedio y percentil 99 de una serie de tiempos
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct Stats {
+    pub min: Duration,
+    pub avg: Duration,
+    pub p99: Duration,
+}
+
+impl Stats {
+    pub fn from_samples(samples: impl Iterator<Item = Duration>) -> Stats {
+        let mut sorted: Vec<Duration> = samples.collect();
+        if sorted.is_empty() {
+            return Stats::default();
+        }
+        sorted.sort();
+        let p99_index = (sorted.len() as f32 * 0.99).ceil() as usize - 1;
+        Stats {
+            min: sorted[0],
+            avg: sorted.iter().sum::<Duration>() / sorted.len() as u32,
+            p99: sorted[p99_index.min(sorted.len() - 1)],
+        }
+    }
+}
+
+// Perfilador de cuadros: acumula el tiempo de cada fase y guarda los últimos cuadros para
+// dibujar el gráfico y exportar estadísticas
+pub struct Profiler {
+    pub visible: bool,
+    history: VecDeque<FrameSample>,
+    current: FrameSample,
+}
+
+impl Profiler {
+    pub fn new(visible: bool) -> Profiler {
+        Profiler { visible, history: VecDeque::with_capacity(HISTORY), current: FrameSample::default() }
+    }
+
+    pub fn record(&mut self, phase: Phase, duration: Duration) {
+        self.current.phases[phase as usize] += duration;
+    }
+
+    pub fn record_render(&mut self, timings: RenderTimings) {
+        self.record(Phase::RayCast, timings.ray_cast);
+        self.record(Phase::WallDraw, timings.wall_draw);
+        self.record(Phase::FloorDraw, timings.floor_draw);
+        self.record(Phase::Hud, timings.hud);
+    }
+
+    // Cierra el cuadro en curso con su duración total
+    pub fn end_frame(&mut self, total: Duration) {
+        let mut sample = std::mem::take(&mut self.current);
+        sample.total = total;
+        if self.history.len() == HISTORY {
+            self.history.pop_front();
+        }
+        self.history.push_back(sample);
+    }
+
+    pub fn samples(&self) -> impl Iterator<Item = &FrameSample> {
+        self.history.iter()
+    }
+
+    pub fn frame_stats(&self) -> Stats {
+        Stats::from_samples(self.history.iter().map(|sample| sample.total))
+    }
+
+    pub fn phase_stats(&self, phase: Phase) -> Stats {
+        Stats::from_samples(self.history.iter().map(|sample| sample.phase(phase)))
+    }
+
+    // Estadísticas en CSV, en milisegundos, para comparar compilaciones
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("phase,min_ms,avg_ms,p99_ms\n");
+        let rows = std::iter::once(("frame", self.frame_stats()))
+            .chain(Phase::ALL.iter().map(|&phase| (phase.name(), self.phase_stats(phase))));
+        for (name, stats) in rows {
+            let _ = writeln!(csv, "{},{:.3},{:.3},{:.3}", name, ms(stats.min), ms(stats.avg), ms(stats.p99));
+        }
+        csv
+    }
+
+    pub fn write_csv(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, self.to_csv())
+    }
+
+    // Gráfico de los últimos cuadros, apilado por fase, y la tabla de tiempos en la esquina superior
+    // izquierda. Sin fuente solo se dibuja el gráfico.
+    pub fn draw_overlay(&self, framebuffer: &mut FrameBuffer, font: Option<&Font>) {
+        let text_lines = if font.is_some() { Phase::ALL.len() + 1 } else { 0 };
+        let panel_width = GRAPH_FRAMES + 8;
+        let panel_height = GRAPH_HEIGHT + 8 + text_lines * LINE_HEIGHT;
+
+        // Fondo oscurecido para que se lea sobre cualquier escena
+        for y in 0..panel_height.min(framebuffer.height) {
+            let row = y * framebuffer.width;
+            for pixel in &mut framebuffer.buffer[row..row + panel_width.min(framebuffer.width)] {
+                *pixel = (*pixel >> 2) & 0x003F_3F3F;
+            }
+        }
+
+        // Una columna por cuadro, de abajo hacia arriba en el orden de las fases; el resto es espera
+        let bottom = 4 + GRAPH_HEIGHT;
+        let to_pixels = |duration: Duration| (ms(duration) / GRAPH_SCALE_MS * GRAPH_HEIGHT as f32) as usize;
+        let skip = self.history.len().saturating_sub(GRAPH_FRAMES);
+        for (column, sample) in self.history.iter().skip(skip).enumerate() {
+            let x = 4 + column;
+            let mut y = bottom;
+            for phase in Phase::ALL {
+                let height = to_pixels(sample.phase(phase)).min(y - 4);
+                fill(framebuffer, x, y - height, 1, height, phase.color());
+                y -= height;
+            }
+            let idle = to_pixels(sample.total).min(GRAPH_HEIGHT).saturating_sub(bottom - y);
+            fill(framebuffer, x, y - idle, 1, idle, Color::new(90, 90, 90));
+        }
+        // Referencia de 60 FPS
+        let target = bottom - to_pixels(Duration::from_micros(16_667));
+        fill(framebuffer, 4, target, GRAPH_FRAMES, 1, Color::new(255, 255, 255));
+
+        let Some(font) = font else {
+            return;
+        };
+        let mut y = bottom + 4;
+        let frame = self.frame_stats();
+        let header = format!("frame  min {:.1}  avg {:.1}  p99 {:.1} ms", ms(frame.min), ms(frame.avg), ms(frame.p99));
+        draw_text(framebuffer, font, &header, 4, y, Color::new(255, 255, 255));
+        for phase in Phase::ALL {
+            y += LINE_HEIGHT;
+            let stats = self.phase_stats(phase);
+            fill(framebuffer, 4, y + 3, 8, 8, phase.color());
+            let figures = format!("avg {:.2}  p99 {:.2} ms", ms(stats.avg), ms(stats.p99));
+            draw_text(framebuffer, font, phase.name(), 16, y, Color::new(230, 230, 230));
+            draw_text(framebuffer, font, &figures, 90, y, Color::new(230, 230, 230));
+        }
+    }
+}
+
+fn ms(duration: Duration) -> f32 {
+    duration.as_secs_f32() * 1000.0
+}
+
+// Rectángulo recortado al framebuffer, sin contar como escritura fuera de rango
+fn fill(framebuffer: &mut FrameBuffer, x: usize, y: usize, width: usize, height: usize, color: Color) {
+    framebuffer.set_current_color(color);
+    framebuffer.draw_rectangle(x, y, width, height);
+}
+
+// Texto con antialiasing: cada píxel se mezcla con el fondo según la cobertura del glifo
+fn draw_text(framebuffer: &mut FrameBuffer, font: &Font, text: &str, x: usize, y: usize, color: Color) {
+    let scale = Scale::uniform(TEXT_SIZE);
+    let ascent = font.v_metrics(scale).ascent;
+    for glyph in font.layout(text, scale, point(x as f32, y as f32 + ascent)) {
+        let Some(bounds) = glyph.pixel_bounding_box() else {
+            continue;
+        };
+        glyph.draw(|gx, gy, coverage| {
+            let px = bounds.min.x + gx as i32;
+            let py = bounds.min.y + gy as i32;
+            if px >= 0 && py >= 0 && (px as usize) < framebuffer.width && (py as usize) < framebuffer.height {
+                let (px, py) = (px as usize, py as usize);
+                let blended = framebuffer.get_color(px, py).lerp(color, coverage);
+                framebuffer.set_pixel(px, py, blended);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats_report_min_average_and_p99() {
+        let samples = (1..=100).map(Duration::from_millis);
+        let stats = Stats::from_samples(samples);
+        assert_eq!(stats.min, Duration::from_millis(1));
+        assert_eq!(stats.avg, Duration::from_micros(50_500));
+        assert_eq!(stats.p99, Duration::from_millis(99));
+        assert_eq!(Stats::from_samples(std::iter::empty()), Stats::default());
+    }
+
+    #[test]
+    fn keeps_a_rolling_window_and_exports_csv() {
+        let mut profiler = Profiler::new(false);
+        for frame in 0..HISTORY + 10 {
+            profiler.record(Phase::RayCast, Duration::from_millis(2));
+            profiler.record(Phase::RayCast, Duration::from_millis(1));
+            profiler.end_frame(Duration::from_millis(10 + (frame % 2) as u64 * 10));
+        }
+        assert_eq!(profiler.samples().count(), HISTORY);
+
+        let csv = profiler.to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "phase,min_ms,avg_ms,p99_ms");
+        assert_eq!(lines[1], "frame,10.000,15.000,20.000");
+        assert_eq!(lines[3], "ray_cast,3.000,3.000,3.000");
+        assert_eq!(lines.len(), 2 + Phase::ALL.len());
+    }
+
+    #[test]
+    fn overlay_fits_in_small_frames() {
+        let mut profiler = Profiler::new(true);
+        profiler.record(Phase::Present, Duration::from_millis(40));
+        profiler.end_frame(Duration::from_millis(50));
+        let mut framebuffer = FrameBuffer::new(64, 48);
+        framebuffer.set_clip_policy(crate::framebuffer::ClipPolicy::Debug);
+        profiler.draw_overlay(&mut framebuffer, None);
+        assert_eq!(framebuffer.end_frame(), 0);
+    }
+}
@@ -0,0 +1,362 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender, TrySendError};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, Frame, RgbaImage};
+use crate::capture::{save_frame, timestamp};
+use crate::framebuffer::FrameBuffer;
+
+// Cuadros que pueden esperar al hilo de escritura; si el codificador se atrasa más, los siguientes
+// se descartan en vez de acumular copias sin límite
+const QUEUE_FRAMES: usize = 8;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RecordingFormat {
+    PngSequence,
+    BmpSequence,
+    Gif,
+}
+
+impl RecordingFormat {
+    pub fn parse(value: &str) -> Option<RecordingFormat> {
+        match value.to_ascii_lowercase().as_str() {
+            "png" => Some(RecordingFormat::PngSequence),
+            "bmp" => Some(RecordingFormat::BmpSequence),
+            "gif" => Some(RecordingFormat::Gif),
+            _ => None,
+        }
+    }
+}
+
+// Copia de un cuadro que se envía al hilo que escribe a disco
+struct CapturedFrame {
+    width: usize,
+    height: usize,
+    pixels: Vec<u32>,
+    // Cuadros descartados justo antes de este; su tiempo se suma al cuadro anterior
+    skipped: u32,
+}
+
+enum WriterMessage {
+    Frame(CapturedFrame),
+    // Cuadros descartados al final de la grabación, que alargan el último
+    Skipped(u32),
+}
+
+// Resultado de una grabación terminada
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordingSummary {
+    pub output: PathBuf,
+    pub frames: usize,
+    // Cuadros que tocaba guardar pero se descartaron porque el codificador iba atrasado
+    pub dropped: usize,
+}
+
+struct Recording {
+    output: PathBuf,
+    frames: usize,
+    dropped: usize,
+    // Descartados desde el último cuadro enviado
+    skipped: u32,
+    last_capture: Option<Instant>,
+    sender: SyncSender<WriterMessage>,
+    writer: JoinHandle<Result<(), String>>,
+}
+
+// Graba los cuadros presentados a una secuencia numerada de imágenes o a un GIF animado.
+// La codificación ocurre en un hilo aparte para no frenar el juego.
+pub struct Recorder {
+    pub format: RecordingFormat,
+    pub fps: f32,
+    pub max_frames: Option<usize>,
+    recording: Option<Recording>,
+}
+
+impl Recorder {
+    pub fn new(format: RecordingFormat, fps: f32, max_frames: Option<usize>) -> Recorder {
+        Recorder {
+            format,
+            fps: fps.max(1.0),
+            max_frames,
+            recording: None,
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    // Inicia una grabación dentro de `dir`; devuelve la carpeta o el archivo de salida
+    pub fn start(&mut self, dir: &Path) -> Result<PathBuf, Box<dyn Error>> {
+        if let Some(recording) = &self.recording {
+            return Ok(recording.output.clone());
+        }
+        std::fs::create_dir_all(dir)?;
+        let name = format!("recording_{}", timestamp());
+
+        if self.format == RecordingFormat::Gif {
+            let output = dir.join(format!("{}.gif", name));
+            let file = BufWriter::new(File::create(&output)?);
+            self.start_gif(output.clone(), file);
+            return Ok(output);
+        }
+        // Secuencia numerada: una imagen por cuadro, sin tiempos
+        let (sender, receiver) = sync_channel::<WriterMessage>(QUEUE_FRAMES);
+        let output = dir.join(&name);
+        std::fs::create_dir_all(&output)?;
+        let extension = if self.format == RecordingFormat::PngSequence { "png" } else { "bmp" };
+        let frames_dir = output.clone();
+        let writer = std::thread::spawn(move || {
+            let frames = receiver.into_iter().filter_map(|message| match message {
+                WriterMessage::Frame(frame) => Some(frame),
+                WriterMessage::Skipped(_) => None,
+            });
+            for (index, frame) in frames.enumerate() {
+                let mut framebuffer = FrameBuffer::new(frame.width, frame.height);
+                framebuffer.buffer = frame.pixels;
+                let path = frames_dir.join(format!("frame_{:05}.{}", index + 1, extension));
+                save_frame(&framebuffer, &path).map_err(|e| e.to_string())?;
+            }
+            Ok(())
+        });
+
+        self.recording = Some(Recording::new(output.clone(), sender, writer));
+        Ok(output)
+    }
+
+    // Codifica el GIF en otro hilo. Cada cuadro se retiene hasta recibir el siguiente, para sumarle
+    // la duración de los que se descartaron en medio y que el GIF dure lo mismo que la grabación.
+    fn start_gif<W: Write + Send + 'static>(&mut self, output: PathBuf, file: W) {
+        let (sender, receiver) = sync_channel::<WriterMessage>(QUEUE_FRAMES);
+        let fps = self.fps.round() as u32;
+        let writer = std::thread::spawn(move || encode_gif(receiver, file, fps));
+        self.recording = Some(Recording::new(output, sender, writer));
+    }
+
+    // Detiene la grabación y espera a que se terminen de escribir los cuadros pendientes
+    pub fn stop(&mut self) -> Result<Option<RecordingSummary>, Box<dyn Error>> {
+        let Some(recording) = self.recording.take() else {
+            return Ok(None);
+        };
+        if recording.skipped > 0 {
+            // Si el hilo ya terminó con error, `join` lo informa
+            let _ = recording.sender.send(WriterMessage::Skipped(recording.skipped));
+        }
+        drop(recording.sender);
+        match recording.writer.join() {
+            Ok(Ok(())) => Ok(Some(RecordingSummary {
+                output: recording.output,
+                frames: recording.frames,
+                dropped: recording.dropped,
+            })),
+            Ok(Err(e)) => Err(e.into()),
+            Err(_) => Err("recording writer thread panicked".into()),
+        }
+    }
+
+    // Llamar con cada cuadro presentado; solo guarda los que tocan según `fps`.
+    // Devuelve el resultado de `stop` cuando se alcanza `max_frames`.
+    pub fn capture(&mut self, framebuffer: &FrameBuffer) -> Result<Option<RecordingSummary>, Box<dyn Error>> {
+        self.capture_at(framebuffer, Instant::now())
+    }
+
+    pub fn capture_at(
+        &mut self,
+        framebuffer: &FrameBuffer,
+        now: Instant,
+    ) -> Result<Option<RecordingSummary>, Box<dyn Error>> {
+        let interval = Duration::from_secs(1).div_f64(self.fps as f64);
+        let Some(recording) = self.recording.as_mut() else {
+            return Ok(None);
+        };
+
+        let due = recording.last_capture.is_none_or(|last| now.duration_since(last) >= interval);
+        if due {
+            // Avanza en pasos fijos para que el ritmo no derive con la duración de cada cuadro
+            recording.last_capture = Some(match recording.last_capture {
+                Some(last) if now.duration_since(last) < interval * 2 => last + interval,
+                _ => now,
+            });
+            let frame = CapturedFrame {
+                width: framebuffer.width,
+                height: framebuffer.height,
+                pixels: framebuffer.buffer.clone(),
+                skipped: recording.skipped,
+            };
+            match recording.sender.try_send(WriterMessage::Frame(frame)) {
+                Ok(()) => {
+                    recording.frames += 1;
+                    recording.skipped = 0;
+                }
+                Err(TrySendError::Full(_)) => {
+                    recording.dropped += 1;
+                    recording.skipped += 1;
+                }
+                // El hilo de escritura terminó con error; `stop` lo informa
+                Err(TrySendError::Disconnected(_)) => return self.stop(),
+            }
+        }
+
+        if self.max_frames.is_some_and(|max| recording.frames >= max) {
+            return self.stop();
+        }
+        Ok(None)
+    }
+}
+
+impl Recording {
+    fn new(output: PathBuf, sender: SyncSender<WriterMessage>, writer: JoinHandle<Result<(), String>>) -> Recording {
+        Recording { output, frames: 0, dropped: 0, skipped: 0, last_capture: None, sender, writer }
+    }
+}
+
+fn encode_gif<W: Write>(receiver: Receiver<WriterMessage>, file: W, fps: u32) -> Result<(), String> {
+    let mut encoder = GifEncoder::new_with_speed(file, 10);
+    encoder.set_repeat(Repeat::Infinite).map_err(|e| e.to_string())?;
+    // Cuadro pendiente y cuántos intervalos lleva en pantalla
+    let mut held: Option<(RgbaImage, u32)> = None;
+    let mut encode = |image: RgbaImage, intervals: u32| {
+        let delay = Delay::from_numer_denom_ms(1000 * intervals, fps);
+        encoder.encode_frame(Frame::from_parts(image, 0, 0, delay)).map_err(|e| e.to_string())
+    };
+
+    for message in receiver {
+        match message {
+            WriterMessage::Frame(frame) => {
+                if let Some((image, intervals)) = held.take() {
+                    encode(image, intervals + frame.skipped)?;
+                }
+                let image = RgbaImage::from_fn(frame.width as u32, frame.height as u32, |x, y| {
+                    let pixel = frame.pixels[frame.width * y as usize + x as usize];
+                    image::Rgba([(pixel >> 16) as u8, (pixel >> 8) as u8, pixel as u8, 255])
+                });
+                held = Some((image, 1));
+            }
+            WriterMessage::Skipped(skipped) => {
+                if let Some((_, intervals)) = held.as_mut() {
+                    *intervals += skipped;
+                }
+            }
+        }
+    }
+    if let Some((image, intervals)) = held {
+        encode(image, intervals)?;
+    }
+    Ok(())
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        if let Err(e) = self.stop() {
+            eprintln!("Error finishing recording: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("ray_casting_{}_{}", name, std::process::id()))
+    }
+
+    #[test]
+    fn captures_frames_at_the_chosen_rate() {
+        let dir = temp_dir("recorder_pacing");
+        let mut recorder = Recorder::new(RecordingFormat::BmpSequence, 10.0, None);
+        let output = recorder.start(&dir).unwrap();
+        let framebuffer = FrameBuffer::new(2, 2);
+        let start = Instant::now();
+
+        // A 10 FPS toca un cuadro cada 100 ms, en pasos fijos aunque los cuadros lleguen tarde
+        // (210 cuenta como 200, así que 300 también toca); tras una pausa larga el ritmo se
+        // reinicia en 900 en vez de ponerse al día
+        for ms in [0, 50, 99, 100, 130, 210, 290, 300, 900, 950, 1000] {
+            let result = recorder.capture_at(&framebuffer, start + Duration::from_millis(ms)).unwrap();
+            assert!(result.is_none());
+        }
+
+        let summary = recorder.stop().unwrap().unwrap();
+        assert_eq!(summary.output, output);
+        assert_eq!((summary.frames, summary.dropped), (6, 0));
+        assert_eq!(std::fs::read_dir(&output).unwrap().count(), 6);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    // Destino que bloquea la primera escritura hasta que la prueba lo libera, como un disco lento
+    struct StalledWriter {
+        release: std::sync::mpsc::Receiver<()>,
+        released: bool,
+        bytes: std::sync::Arc<std::sync::Mutex<Vec<u8>>>,
+    }
+
+    impl Write for StalledWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            if !self.released {
+                self.release.recv().ok();
+                self.released = true;
+            }
+            self.bytes.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn dropped_frames_keep_the_gif_in_real_time() {
+        use image::AnimationDecoder;
+
+        let (release, stalled) = std::sync::mpsc::channel();
+        let bytes = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let writer = StalledWriter { release: stalled, released: false, bytes: bytes.clone() };
+        let mut recorder = Recorder::new(RecordingFormat::Gif, 10.0, None);
+        recorder.start_gif(PathBuf::from("stalled.gif"), writer);
+
+        // 30 cuadros a 10 FPS son 3 segundos; con el codificador detenido la cola se llena
+        let framebuffer = FrameBuffer::new(2, 2);
+        let start = Instant::now();
+        for ms in (0..30).map(|i| i * 100) {
+            recorder.capture_at(&framebuffer, start + Duration::from_millis(ms)).unwrap();
+        }
+        release.send(()).unwrap();
+        let summary = recorder.stop().unwrap().unwrap();
+        assert!(summary.dropped >= 30 - QUEUE_FRAMES - 2, "{:?}", summary);
+        assert_eq!(summary.frames + summary.dropped, 30);
+
+        let bytes = bytes.lock().unwrap().clone();
+        let decoder = image::codecs::gif::GifDecoder::new(std::io::Cursor::new(bytes)).unwrap();
+        let frames = decoder.into_frames().collect_frames().unwrap();
+        assert_eq!(frames.len(), summary.frames);
+        let total_ms: f64 = frames
+            .iter()
+            .map(|frame| {
+                let (numer, denom) = frame.delay().numer_denom_ms();
+                numer as f64 / denom as f64
+            })
+            .sum();
+        assert!((total_ms - 3000.0).abs() < 1.0, "total = {} ms", total_ms);
+    }
+
+    #[test]
+    fn stops_at_the_frame_limit() {
+        let dir = temp_dir("recorder_limit");
+        let mut recorder = Recorder::new(RecordingFormat::Gif, 10.0, Some(2));
+        recorder.start(&dir).unwrap();
+        let framebuffer = FrameBuffer::new(2, 2);
+        let start = Instant::now();
+
+        assert!(recorder.capture_at(&framebuffer, start).unwrap().is_none());
+        let summary = recorder.capture_at(&framebuffer, start + Duration::from_millis(100)).unwrap().unwrap();
+        assert_eq!(summary.frames, 2);
+        assert!(!recorder.is_recording());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,276 @@
+use std::time::{Duration, Instant};
+use image::GrayImage;
+use crate::caster::cast_ray;
+use crate::color::Color;
+use crate::framebuffer::FrameBuffer;
+use crate::maze::WORLD_WIDTH;
+use crate::player::Player;
+
+pub fn block_size(maze: &[Vec<char>]) -> usize {
+    WORLD_WIDTH / maze[0].len()
+}
+
+fn cell_color(cell: char) -> Color {
+    match cell {
+        '-' => Color::new(105, 105, 105),
+        '|' => Color::new(135, 135, 135),
+        '+' => Color::new(115, 115, 115),
+        'p' => Color::new(255, 255, 0),
+        'g' => Color::new(255, 165, 0),
+        // Pared húmeda que gotea
+        'd' => Color::new(70, 100, 140),
+        _ => Color::new(0, 50, 0),
+    }
+}
+
+pub fn render2d(framebuffer: &mut FrameBuffer, maze: &[Vec<char>], player: &Player) {
+    let block_size = block_size(maze);
+    // Factor entre las coordenadas del mundo y las del framebuffer, que puede tener menor resolución
+    let scale = framebuffer.width as f32 / WORLD_WIDTH as f32;
+    let to_screen = |v: f32| (v * scale).round() as i32;
+
+    // Dibuja el laberinto
+    for (row, cells) in maze.iter().enumerate() {
+        for (col, &cell) in cells.iter().enumerate() {
+            let color = cell_color(cell);
+
+            let x = to_screen((col * block_size) as f32) as usize;
+            let y = to_screen((row * block_size) as f32) as usize;
+            let size = to_screen(((col + 1) * block_size) as f32) as usize - x;
+
+            if x + size <= framebuffer.width && y + size <= framebuffer.height {
+                framebuffer.set_current_color(color);
+                framebuffer.draw_rectangle(x, y, size, size);
+            }
+        }
+    }
+
+    // Dibuja los rayos desde el jugador hasta el punto de impacto
+    framebuffer.set_current_color(Color::new(255, 0, 0));
+    let num_rays = 50;
+    for i in 0..num_rays {
+        let current_ray = i as f32 / num_rays as f32;
+        let a = player.a - (player.fov / 2.0) + (player.fov * current_ray);
+        let intersect = cast_ray(maze, player, a, block_size);
+        framebuffer.draw_line_aa(
+            player.pos.x * scale,
+            player.pos.y * scale,
+            (player.pos.x + intersect.distance * a.cos()) * scale,
+            (player.pos.y + intersect.distance * a.sin()) * scale,
+        );
+    }
+
+    // Dibuja el jugador como una flecha que apunta en la dirección de la vista
+    let arrow_size = block_size as f32 / 4.0;
+    let vertex = |angle: f32, length: f32| {
+        (
+            to_screen(player.pos.x + length * angle.cos()),
+            to_screen(player.pos.y + length * angle.sin()),
+        )
+    };
+    framebuffer.set_current_color(Color::new(255, 255, 255));
+    framebuffer.fill_triangle(
+        vertex(player.a, arrow_size),
+        vertex(player.a + 2.5, arrow_size * 0.6),
+        vertex(player.a - 2.5, arrow_size * 0.6),
+    );
+}
+
+
+// Tiempo de cada pasada de la vista 3D. Con varios hilos, el de la franja más lenta.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct RenderTimings {
+    pub ray_cast: Duration,
+    pub wall_draw: Duration,
+    pub floor_draw: Duration,
+    // Minimapa
+    pub hud: Duration,
+}
+
+impl RenderTimings {
+    fn max(self, other: RenderTimings) -> RenderTimings {
+        RenderTimings {
+            ray_cast: self.ray_cast.max(other.ray_cast),
+            wall_draw: self.wall_draw.max(other.wall_draw),
+            floor_draw: self.floor_draw.max(other.floor_draw),
+            hud: self.hud.max(other.hud),
+        }
+    }
+}
+
+pub fn render3d(framebuffer: &mut FrameBuffer, maze: &[Vec<char>], player: &Player, texture: &GrayImage) {
+    render3d_threaded(framebuffer, maze, player, texture, 1);
+}
+
+// Como `render3d`, pero reparte las columnas de la vista en `threads` franjas verticales, una por hilo.
+// Cada hilo dibuja su franja en un búfer propio que luego se copia al framebuffer, así que el
+// resultado es idéntico al de un solo hilo.
+pub fn render3d_threaded(
+    framebuffer: &mut FrameBuffer,
+    maze: &[Vec<char>],
+    player: &Player,
+    texture: &GrayImage,
+    threads: usize,
+) -> RenderTimings {
+    let block_size = block_size(maze);
+    let (width, height) = (framebuffer.width, framebuffer.height);
+    let threads = threads.clamp(1, width.max(1));
+    let stripe_width = width.div_ceil(threads);
+
+    let mut stripes: Vec<(usize, Vec<u32>)> = (0..width)
+        .step_by(stripe_width.max(1))
+        .map(|x0| (x0, vec![0; stripe_width.min(width - x0) * height]))
+        .collect();
+
+    // Con un solo hilo no vale la pena lanzar ninguno
+    let mut timings = if let [(x0, stripe)] = stripes.as_mut_slice() {
+        draw_columns(stripe, *x0, width, height, maze, player, texture, block_size)
+    } else {
+        std::thread::scope(|scope| {
+            let workers: Vec<_> = stripes
+                .iter_mut()
+                .map(|(x0, stripe)| {
+                    let x0 = *x0;
+                    scope.spawn(move || draw_columns(stripe, x0, width, height, maze, player, texture, block_size))
+                })
+                .collect();
+            workers
+                .into_iter()
+                .map(|worker| worker.join().expect("render thread panicked"))
+                .fold(RenderTimings::default(), RenderTimings::max)
+        })
+    };
+
+    // Copia cada franja a su lugar en el framebuffer; cuenta como parte de dibujar las paredes
+    let copy_start = Instant::now();
+    for (x0, stripe) in &stripes {
+        let columns = stripe.len() / height.max(1);
+        for (y, row) in stripe.chunks(columns.max(1)).enumerate() {
+            let start = y * width + x0;
+            framebuffer.buffer[start..start + columns].copy_from_slice(row);
+        }
+    }
+    timings.wall_draw += copy_start.elapsed();
+
+    // Dibuja el minimapa en la esquina inferior derecha
+    let hud_start = Instant::now();
+    // Tamaño del minimapa, proporcional a la resolución; en vistas muy bajas se limita al alto
+    let minimap_size = (framebuffer.width * 3 / 20).min(framebuffer.height);
+    let minimap_x = framebuffer.width - minimap_size;
+    let minimap_y = framebuffer.height - minimap_size;
+
+    // Dibuja el minimapa fondo
+    framebuffer.set_current_color(Color::new(0, 0, 0));
+    framebuffer.draw_rectangle(minimap_x, minimap_y, minimap_size, minimap_size);
+
+    // Dibuja el laberinto en el minimapa
+    let minimap_block_size = minimap_size / maze[0].len();
+    for (row, cells) in maze.iter().enumerate() {
+        for (col, &cell) in cells.iter().enumerate() {
+            let color = cell_color(cell);
+
+            let x = minimap_x + col * minimap_block_size;
+            let y = minimap_y + row * minimap_block_size;
+
+            if x + minimap_block_size <= framebuffer.width && y + minimap_block_size <= framebuffer.height {
+                framebuffer.set_current_color(color);
+                framebuffer.draw_rectangle(x, y, minimap_block_size, minimap_block_size);
+            }
+        }
+    }
+
+    // Dibuja la ubicación del jugador en el minimapa
+    let player_minimap_x = minimap_x + (player.pos.x / block_size as f32 * minimap_block_size as f32) as usize;
+    let player_minimap_y = minimap_y + (player.pos.y / block_size as f32 * minimap_block_size as f32) as usize;
+    framebuffer.set_current_color(Color::new(255, 0, 0)); // Color del jugador en el minimapa
+    framebuffer.set_pixel(player_minimap_x, player_minimap_y, framebuffer.current_color);
+    timings.hud = hud_start.elapsed();
+    timings
+}
+
+// Cielo, suelo y paredes de las columnas `x0..` de la vista, en `stripe`, una franja de
+// `stripe.len() / height` columnas guardada fila por fila
+#[allow(clippy::too_many_arguments)]
+fn draw_columns(
+    stripe: &mut [u32],
+    x0: usize,
+    width: usize,
+    height: usize,
+    maze: &[Vec<char>],
+    player: &Player,
+    texture: &GrayImage,
+    block_size: usize,
+) -> RenderTimings {
+    let num_rays = width;
+    let hh = height as f32 / 2.0;
+    let distance_to_projection_plane = 100.0;
+    let columns = stripe.len() / height.max(1);
+    // El cabeceo desplaza el horizonte sin inclinar las paredes (y-shearing)
+    let horizon = hh + player.pitch * height as f32;
+
+    let ray_angle = |i: usize| player.a - (player.fov / 2.0) + (player.fov * (i as f32 / num_rays as f32));
+
+    // Primero, el techo arriba del horizonte y el suelo abajo. Cada fila corta el plano a una
+    // distancia fija: aquella a la que una pared tendría ahí su borde, según el horizonte y la
+    // altura de los ojos. Las baldosas alternan por celda y se oscurecen con la distancia.
+    let start = Instant::now();
+    let directions: Vec<(f32, f32)> = (x0..x0 + columns).map(|i| (ray_angle(i).cos(), ray_angle(i).sin())).collect();
+    let ceiling = [Color::new(0, 0, 70), Color::new(0, 0, 50)];
+    let floor = [Color::new(0, 70, 0), Color::new(0, 50, 0)];
+    let block = block_size as f32;
+    for (y, row) in stripe.chunks_mut(columns.max(1)).enumerate() {
+        // Centro de la fila respecto del horizonte; nunca es 0
+        let offset = y as f32 + 0.5 - horizon;
+        let (tiles, eye_to_plane) = if offset < 0.0 {
+            (ceiling, 1.0 - player.eye_height)
+        } else {
+            (floor, player.eye_height)
+        };
+        let distance = hh * distance_to_projection_plane * eye_to_plane.max(0.0) / offset.abs();
+        let shade = 1.0 / (1.0 + distance / (8.0 * block));
+        let tiles = tiles.map(|tile| (tile * shade).to_hex());
+
+        for (pixel, (cos, sin)) in row.iter_mut().zip(&directions) {
+            let cell_x = ((player.pos.x + distance * cos) / block).floor() as i64;
+            let cell_y = ((player.pos.y + distance * sin) / block).floor() as i64;
+            // Paridad de la suma sin desbordar cuando la distancia es enorme
+            *pixel = tiles[((cell_x ^ cell_y) & 1) as usize];
+        }
+    }
+    let floor_draw = start.elapsed();
+
+    let start = Instant::now();
+    let heights: Vec<usize> = (x0..x0 + columns)
+        .map(|i| {
+            let intersect = cast_ray(maze, player, ray_angle(i), block_size);
+
+            let distance_to_wall = intersect.distance;
+            // Con la cámara dentro de una pared la distancia es 0; se limita la altura para no
+            // desbordar al pasarla a filas
+            (((hh / distance_to_wall) * distance_to_projection_plane) as usize).min(height * 8)
+        })
+        .collect();
+    let ray_cast = start.elapsed();
+
+    let start = Instant::now();
+    for (column, &stake_height) in heights.iter().enumerate() {
+        let i = x0 + column;
+        // Con los ojos a media altura la pared queda centrada en el horizonte; más arriba se ve
+        // más pared por debajo del horizonte y menos por encima
+        let stake_top = (horizon - stake_height as f32 * (1.0 - player.eye_height)).floor() as i64;
+        let stake_bottom = ((horizon + stake_height as f32 * player.eye_height).max(0.0) as usize).min(height);
+
+        // La textura se recorta junto con la pared si esta sobresale por arriba
+        for y in stake_top.max(0) as usize..stake_bottom {
+            let texture_y = ((y as i64 - stake_top) as f32 / stake_height as f32 * texture.height() as f32) as u32;
+            let texture_y = texture_y.min(texture.height() - 1);
+
+            let pixel = texture.get_pixel(i.min(texture.width() as usize - 1) as u32, texture_y);
+            let luminance = pixel[0]; // La imagen en escala de grises tiene solo un componente
+            stripe[y * columns + column] = Color::new(luminance, luminance, luminance).to_hex();
+        }
+    }
+    let wall_draw = start.elapsed();
+
+    RenderTimings { ray_cast, wall_draw, floor_draw, hud: Duration::ZERO }
+}
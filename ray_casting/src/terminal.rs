@@ -0,0 +1,267 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+use crossterm::event::{
+    self, Event, KeyCode, KeyEventKind, KeyModifiers, KeyboardEnhancementFlags, PopKeyboardEnhancementFlags,
+    PushKeyboardEnhancementFlags,
+};
+use crossterm::{cursor, execute, terminal};
+use crate::capture::ImageFormat;
+use crate::color::Color;
+use crate::framebuffer::FrameBuffer;
+use crate::platform::{Actions, FrameInput, Platform};
+use crate::player::InputState;
+
+// Rampa de caracteres de más oscuro a más claro
+const ASCII_RAMP: &[u8] = b" .:-=+*#%@";
+// Sin eventos de tecla soltada, una tecla se considera presionada hasta este tiempo después
+// de su última repetición; cubre la demora inicial de la repetición del teclado
+const HOLD_TIME: Duration = Duration::from_millis(500);
+const HELP: &str = "arrows: move  PgUp/PgDn: look  d: 2D/3D  m: mute  f: profiler  F12: screenshot  F10: record  q: quit";
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TerminalStyle {
+    // Medio bloque '▀' con color verdadero: dos píxeles por celda
+    HalfBlock,
+    // Caracteres según la luminancia, sin color
+    Ascii,
+}
+
+// Plataforma para jugar por SSH: dibuja en la terminal y lee el teclado en modo crudo
+pub struct TerminalPlatform {
+    style: TerminalStyle,
+    start: Instant,
+    open: bool,
+    // Teclas presionadas y cuándo se vieron por última vez
+    held: HashMap<KeyCode, Instant>,
+    // La terminal informa cuándo se suelta una tecla (protocolo de teclado de kitty)
+    release_events: bool,
+    message: String,
+    output: String,
+}
+
+impl TerminalPlatform {
+    pub fn new(style: TerminalStyle) -> io::Result<TerminalPlatform> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+        let release_events = terminal::supports_keyboard_enhancement().unwrap_or(false);
+        if release_events {
+            execute!(
+                io::stdout(),
+                PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES)
+            )?;
+        }
+        Ok(TerminalPlatform {
+            style,
+            start: Instant::now(),
+            open: true,
+            held: HashMap::new(),
+            release_events,
+            message: String::new(),
+            output: String::new(),
+        })
+    }
+
+    fn is_down(&self, codes: &[KeyCode]) -> bool {
+        codes.iter().any(|code| {
+            self.held
+                .get(code)
+                .is_some_and(|seen| self.release_events || seen.elapsed() < HOLD_TIME)
+        })
+    }
+}
+
+impl Drop for TerminalPlatform {
+    fn drop(&mut self) {
+        let mut stdout = io::stdout();
+        if self.release_events {
+            let _ = execute!(stdout, PopKeyboardEnhancementFlags);
+        }
+        let _ = execute!(stdout, cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+impl Platform for TerminalPlatform {
+    fn is_open(&self) -> bool {
+        self.open
+    }
+
+    fn poll_input(&mut self) -> FrameInput {
+        let mut actions = Actions::default();
+        while event::poll(Duration::ZERO).unwrap_or(false) {
+            let Ok(Event::Key(key)) = event::read() else {
+                continue;
+            };
+            if key.kind == KeyEventKind::Release {
+                self.held.remove(&key.code);
+                continue;
+            }
+            self.held.insert(key.code, Instant::now());
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => actions.quit = true,
+                KeyCode::Char('c') if ctrl => actions.quit = true,
+                KeyCode::Char('d' | 'D') => actions.toggle_view = true,
+                KeyCode::Char('m' | 'M') => actions.toggle_mute = true,
+                KeyCode::F(10) => actions.toggle_recording = true,
+                KeyCode::F(3) | KeyCode::Char('f' | 'F') => actions.toggle_profiler = true,
+                KeyCode::F(4) => actions.export_profile = true,
+                KeyCode::F(12) => {
+                    let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+                    actions.screenshot = Some(if shift { ImageFormat::Bmp } else { ImageFormat::Png });
+                }
+                _ => {}
+            }
+        }
+        if actions.quit {
+            self.open = false;
+        }
+
+        let input = InputState {
+            forward: self.is_down(&[KeyCode::Up]),
+            backward: self.is_down(&[KeyCode::Down]),
+            turn_left: self.is_down(&[KeyCode::Left]),
+            turn_right: self.is_down(&[KeyCode::Right]),
+            look_up: self.is_down(&[KeyCode::PageUp]),
+            look_down: self.is_down(&[KeyCode::PageDown]),
+            mouse_dx: 0.0,
+            mouse_dy: 0.0,
+        };
+        FrameInput { input, actions }
+    }
+
+    fn present(&mut self, frame: &FrameBuffer) -> Result<(), Box<dyn Error>> {
+        let (cols, rows) = terminal::size()?;
+        // La última fila queda para la ayuda y los mensajes
+        let (cols, rows) = (cols as usize, (rows as usize).saturating_sub(1).max(1));
+
+        self.output.clear();
+        encode_frame(frame, cols, rows, self.style, &mut self.output);
+        let status = if self.message.is_empty() { HELP } else { &self.message };
+        let status: String = status.chars().take(cols).collect();
+        write!(self.output, "\x1b[{};1H\x1b[0m{}\x1b[K", rows + 1, status)?;
+
+        let mut stdout = io::stdout().lock();
+        stdout.write_all(self.output.as_bytes())?;
+        stdout.flush()?;
+        Ok(())
+    }
+
+    fn show_message(&mut self, message: &str) {
+        self.message = message.to_string();
+    }
+
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    fn sleep(&mut self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+}
+
+// Reduce el cuadro al tamaño de la terminal, manteniendo la proporción, y lo escribe como
+// secuencias ANSI desde la esquina superior izquierda. Las celdas miden el doble de alto que de ancho.
+pub fn encode_frame(frame: &FrameBuffer, cols: usize, rows: usize, style: TerminalStyle, out: &mut String) {
+    if frame.width == 0 || frame.height == 0 || cols == 0 || rows == 0 {
+        return;
+    }
+    // Escala en "medias celdas": una columna de ancho y media fila de alto
+    let scale = (cols as f32 / frame.width as f32).min((rows * 2) as f32 / frame.height as f32);
+    let out_cols = ((frame.width as f32 * scale) as usize).clamp(1, cols);
+    let out_rows = ((frame.height as f32 * scale / 2.0) as usize).clamp(1, rows);
+
+    out.push_str("\x1b[H");
+    match style {
+        TerminalStyle::HalfBlock => {
+            let mut last = (None, None);
+            for row in 0..out_rows {
+                for col in 0..out_cols {
+                    let top = box_average(frame, col, out_cols, row * 2, out_rows * 2);
+                    let bottom = box_average(frame, col, out_cols, row * 2 + 1, out_rows * 2);
+                    // Solo se cambian los colores cuando hace falta
+                    if last.0 != Some(top) {
+                        let _ = write!(out, "\x1b[38;2;{};{};{}m", top.r, top.g, top.b);
+                    }
+                    if last.1 != Some(bottom) {
+                        let _ = write!(out, "\x1b[48;2;{};{};{}m", bottom.r, bottom.g, bottom.b);
+                    }
+                    last = (Some(top), Some(bottom));
+                    out.push('▀');
+                }
+                out.push_str("\x1b[0m\x1b[K\r\n");
+                last = (None, None);
+            }
+        }
+        TerminalStyle::Ascii => {
+            for row in 0..out_rows {
+                for col in 0..out_cols {
+                    let color = box_average(frame, col, out_cols, row, out_rows);
+                    let luminance = (0.299 * color.r as f32 + 0.587 * color.g as f32 + 0.114 * color.b as f32) / 255.0;
+                    let index = ((luminance * (ASCII_RAMP.len() - 1) as f32).round() as usize).min(ASCII_RAMP.len() - 1);
+                    out.push(ASCII_RAMP[index] as char);
+                }
+                out.push_str("\x1b[K\r\n");
+            }
+        }
+    }
+    // Limpiar lo que quede debajo si la terminal se agrandó
+    out.push_str("\x1b[J");
+}
+
+// Promedio del bloque de píxeles de origen que cae en la celda (col, row) de una cuadrícula cols x rows
+fn box_average(frame: &FrameBuffer, col: usize, cols: usize, row: usize, rows: usize) -> Color {
+    let x0 = col * frame.width / cols;
+    let x1 = ((col + 1) * frame.width / cols).max(x0 + 1);
+    let y0 = row * frame.height / rows;
+    let y1 = ((row + 1) * frame.height / rows).max(y0 + 1);
+
+    let (mut r, mut g, mut b) = (0u32, 0u32, 0u32);
+    for y in y0..y1 {
+        for &pixel in &frame.buffer[y * frame.width + x0..y * frame.width + x1] {
+            r += (pixel >> 16) & 0xFF;
+            g += (pixel >> 8) & 0xFF;
+            b += pixel & 0xFF;
+        }
+    }
+    let count = ((x1 - x0) * (y1 - y0)) as u32;
+    Color::new((r / count) as u8, (g / count) as u8, (b / count) as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn two_tone_frame() -> FrameBuffer {
+        // Mitad superior roja, mitad inferior azul
+        let mut frame = FrameBuffer::new(4, 4);
+        for y in 0..4 {
+            for x in 0..4 {
+                frame.set_pixel(x, y, if y < 2 { Color::new(255, 0, 0) } else { Color::new(0, 0, 255) });
+            }
+        }
+        frame
+    }
+
+    #[test]
+    fn half_blocks_carry_top_and_bottom_colors() {
+        let mut out = String::new();
+        encode_frame(&two_tone_frame(), 1, 1, TerminalStyle::HalfBlock, &mut out);
+        assert_eq!(out, "\x1b[H\x1b[38;2;255;0;0m\x1b[48;2;0;0;255m▀\x1b[0m\x1b[K\r\n\x1b[J");
+    }
+
+    #[test]
+    fn downsamples_keeping_the_aspect_ratio() {
+        let mut out = String::new();
+        // Un cuadro cuadrado en 10x2 celdas ocupa 4 columnas por 2 filas
+        encode_frame(&two_tone_frame(), 10, 2, TerminalStyle::Ascii, &mut out);
+        let lines: Vec<&str> = out.trim_start_matches("\x1b[H").split("\x1b[K\r\n").collect();
+        assert_eq!(lines[..2], ["----", "...."]);
+    }
+}
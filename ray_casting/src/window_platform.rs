@@ -0,0 +1,184 @@
+use std::error::Error;
+use std::time::{Duration, Instant};
+use minifb::{Key, KeyRepeat, MouseMode, Window, WindowOptions};
+use ray_casting::capture::ImageFormat;
+use ray_casting::framebuffer::FrameBuffer;
+use ray_casting::platform::{Actions, FrameInput, Platform};
+use ray_casting::player::InputState;
+
+// Plataforma de escritorio: ventana de minifb, teclado y mouse
+pub struct WindowPlatform {
+    window: Window,
+    width: usize,
+    height: usize,
+    last_mouse: Option<(f32, f32)>,
+    // Mouse capturado: cursor oculto y devuelto al centro en cada cuadro
+    relative_mouse: bool,
+    captured: bool,
+    warp: Option<CursorWarp>,
+    start: Instant,
+}
+
+impl WindowPlatform {
+    pub fn new(title: &str, width: usize, height: usize, relative_mouse: bool) -> Result<WindowPlatform, minifb::Error> {
+        let window = Window::new(title, width, height, WindowOptions::default())?;
+        let last_mouse = window.get_mouse_pos(MouseMode::Pass);
+        let warp = if relative_mouse { CursorWarp::new(&window) } else { None };
+        Ok(WindowPlatform {
+            window,
+            width,
+            height,
+            last_mouse,
+            relative_mouse,
+            captured: false,
+            warp,
+            start: Instant::now(),
+        })
+    }
+
+    // Traduce el teclado y el mouse de la ventana a un `InputState` por tick
+    fn sample_input(&mut self) -> InputState {
+        let window = &self.window;
+        let mut input = InputState {
+            forward: window.is_key_down(Key::Up),
+            backward: window.is_key_down(Key::Down),
+            turn_left: window.is_key_down(Key::Left),
+            turn_right: window.is_key_down(Key::Right),
+            // Re Pág y Av Pág inclinan la cabeza
+            look_up: window.is_key_down(Key::PageUp),
+            look_down: window.is_key_down(Key::PageDown),
+            mouse_dx: 0.0,
+            mouse_dy: 0.0,
+        };
+
+        // Sin foco el mouse queda libre y no gira la vista
+        let capture = self.relative_mouse && self.window.is_active();
+        if capture != self.captured {
+            self.captured = capture;
+            self.window.set_cursor_visibility(!capture);
+            self.last_mouse = None;
+        }
+        if self.relative_mouse && !capture {
+            return input;
+        }
+
+        // En modo Pass la posición sigue llegando aunque el cursor salga de la ventana
+        if let Some(mouse_pos) = self.window.get_mouse_pos(MouseMode::Pass) {
+            // Calcular el cambio en la posición del mouse
+            let last = self.last_mouse.unwrap_or(mouse_pos);
+            input.mouse_dx = mouse_pos.0 - last.0;
+            input.mouse_dy = mouse_pos.1 - last.1;
+            // Actualizar la última posición del mouse
+            self.last_mouse = Some(mouse_pos);
+
+            // Devolver el cursor al centro para que nunca llegue al borde de la pantalla
+            if capture {
+                let center = (self.width as f32 / 2.0, self.height as f32 / 2.0);
+                if let Some(warp) = &self.warp {
+                    if warp.move_to(&self.window, center.0 as i32, center.1 as i32) {
+                        self.last_mouse = Some(center);
+                    }
+                }
+            }
+        }
+
+        input
+    }
+}
+
+impl Platform for WindowPlatform {
+    fn is_open(&self) -> bool {
+        self.window.is_open()
+    }
+
+    fn is_focused(&mut self) -> bool {
+        self.window.is_active()
+    }
+
+    fn poll_input(&mut self) -> FrameInput {
+        let window = &self.window;
+        let pressed = |key| window.is_key_pressed(key, KeyRepeat::No);
+        let shift = window.is_key_down(Key::LeftShift) || window.is_key_down(Key::RightShift);
+
+        let actions = Actions {
+            quit: window.is_key_down(Key::Escape),
+            // D alterna 2D y 3D, M silencia y F10 graba
+            toggle_view: pressed(Key::D),
+            toggle_mute: pressed(Key::M),
+            toggle_recording: pressed(Key::F10),
+            // Captura de pantalla: F12 en PNG, Shift+F12 en BMP
+            screenshot: pressed(Key::F12).then_some(if shift { ImageFormat::Bmp } else { ImageFormat::Png }),
+            // Perfilador: F3 muestra el gráfico y F4 exporta las estadísticas
+            toggle_profiler: pressed(Key::F3),
+            export_profile: pressed(Key::F4),
+        };
+        FrameInput { input: self.sample_input(), actions }
+    }
+
+    fn present(&mut self, frame: &FrameBuffer) -> Result<(), Box<dyn Error>> {
+        Ok(self.window.update_with_buffer(frame.as_u32_slice(), self.width, self.height)?)
+    }
+
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    fn sleep(&mut self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+}
+
+// Mover el cursor dentro de la ventana. minifb no lo permite, así que en X11 se usa Xlib
+// directamente; en otros sistemas (o en Wayland) el cursor solo se oculta y el giro
+// continúa mientras no llegue al borde de la pantalla.
+#[cfg(all(unix, not(target_os = "macos")))]
+struct CursorWarp {
+    xlib: x11_dl::xlib::Xlib,
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+impl CursorWarp {
+    fn new(window: &Window) -> Option<CursorWarp> {
+        // Solo si la ventana es de X11
+        CursorWarp::handles(window)?;
+        x11_dl::xlib::Xlib::open().ok().map(|xlib| CursorWarp { xlib })
+    }
+
+    fn handles(window: &Window) -> Option<(*mut x11_dl::xlib::Display, std::os::raw::c_ulong)> {
+        use raw_window_handle::{HasDisplayHandle, HasWindowHandle, RawDisplayHandle, RawWindowHandle};
+        let display = window.display_handle().ok()?.as_raw();
+        let handle = window.window_handle().ok()?.as_raw();
+        match (display, handle) {
+            (RawDisplayHandle::Xlib(display), RawWindowHandle::Xlib(handle)) => {
+                Some((display.display?.as_ptr().cast(), handle.window))
+            }
+            _ => None,
+        }
+    }
+
+    fn move_to(&self, window: &Window, x: i32, y: i32) -> bool {
+        let Some((display, handle)) = CursorWarp::handles(window) else {
+            return false;
+        };
+        // SAFETY: el display y la ventana pertenecen a la ventana de minifb, que sigue abierta
+        unsafe {
+            (self.xlib.XWarpPointer)(display, 0, handle, 0, 0, 0, 0, x, y);
+            (self.xlib.XFlush)(display);
+        }
+        true
+    }
+}
+
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+struct CursorWarp;
+
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+impl CursorWarp {
+    fn new(_window: &Window) -> Option<CursorWarp> {
+        None
+    }
+
+    fn move_to(&self, _window: &Window, _x: i32, _y: i32) -> bool {
+        false
+    }
+}
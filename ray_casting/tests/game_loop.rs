@@ -0,0 +1,166 @@
+// El bucle completo del juego sobre una plataforma con guion, sin ventana ni audio
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use nalgebra_glm::Vec2;
+use ray_casting::assets::Assets;
+use ray_casting::audio::Audio;
+use ray_casting::config::Config;
+use ray_casting::demo::DemoPlayback;
+use ray_casting::game::{Game, ViewMode};
+use ray_casting::platform::{Actions, FrameInput, Platform, ScriptedPlatform};
+use ray_casting::player::{InputState, Player};
+use ray_casting::profiler::Phase;
+
+fn new_game() -> Game {
+    new_game_with(Config::default())
+}
+
+fn new_game_with(mut config: Config) -> Game {
+    config.display.width = 200;
+    config.display.height = 180;
+    config.paths.asset_root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    config.paths.map = Path::new("tests/fixtures/corridor.txt").to_path_buf();
+    config.audio.enabled = false;
+
+    let mut assets = Assets::new(config.asset_roots());
+    let mut game = Game::new(&config, &mut assets, Audio::disabled());
+    game.player = Player::new(Vec2::new(114.0, 114.0), 0.0);
+    game
+}
+
+fn forward() -> FrameInput {
+    FrameInput { input: InputState { forward: true, ..InputState::default() }, ..FrameInput::default() }
+}
+
+fn action(actions: Actions) -> FrameInput {
+    FrameInput { actions, ..FrameInput::default() }
+}
+
+#[test]
+fn runs_until_the_script_ends() {
+    let mut game = new_game();
+    let mut platform = ScriptedPlatform::repeat(forward(), 60);
+    game.run(&mut platform).unwrap();
+
+    assert_eq!(platform.presented_frames, 60);
+    let frame = platform.last_frame.as_ref().unwrap();
+    assert_eq!((frame.width, frame.height), (200, 180));
+    // El primer cuadro tiene dt = 0; los demás avanzan 16 ms a 300 unidades por segundo
+    assert!((game.player.pos.x - (114.0 + 59.0 * 0.016 * 300.0)).abs() < 0.5, "x = {}", game.player.pos.x);
+    assert_eq!(game.player.pos.y, 114.0);
+}
+
+#[test]
+fn quit_action_stops_the_loop() {
+    let mut game = new_game();
+    let script = std::iter::repeat_n(forward(), 5)
+        .chain([action(Actions { quit: true, ..Actions::default() })])
+        .chain(std::iter::repeat_n(forward(), 5));
+    let mut platform = ScriptedPlatform::new(script);
+    game.run(&mut platform).unwrap();
+
+    assert_eq!(platform.presented_frames, 5);
+    assert!(platform.is_open());
+}
+
+#[test]
+fn view_toggles_between_2d_and_3d() {
+    let mut game = new_game();
+    let toggle = || action(Actions { toggle_view: true, ..Actions::default() });
+    game.run(&mut ScriptedPlatform::new([toggle()])).unwrap();
+    assert_eq!(game.mode, ViewMode::View3D);
+
+    game.run(&mut ScriptedPlatform::new([toggle(), forward()])).unwrap();
+    assert_eq!(game.mode, ViewMode::Map2D);
+    assert_ne!(game.player.pos, Vec2::new(114.0, 114.0));
+}
+
+#[test]
+fn recorded_demo_replays_to_the_same_pose() {
+    let mut game = new_game();
+    game.record_demo(7);
+    let script = (0..90).map(|tick| FrameInput {
+        input: InputState { forward: true, turn_right: (30..40).contains(&tick), ..InputState::default() },
+        ..FrameInput::default()
+    });
+    game.run(&mut ScriptedPlatform::new(script)).unwrap();
+
+    let demo = game.take_demo().unwrap();
+    assert_eq!(demo.ticks.len(), 90);
+    assert_eq!(demo.map.as_deref(), Some(Path::new("tests/fixtures/corridor.txt")));
+    let replayed = demo.replay(game.maze());
+    assert_eq!(replayed.pos, game.player.pos);
+    assert_eq!(replayed.a.to_bits(), game.player.a.to_bits());
+
+    // En el juego la grabación vuelve al laberinto en que se grabó aunque haya otro cargado
+    let mut assets = Assets::new(vec![PathBuf::from(env!("CARGO_MANIFEST_DIR"))]);
+    let mut other = new_game();
+    other.load_level(&mut assets, Path::new("tests/fixtures/rooms.txt")).unwrap();
+    assert_ne!(other.maze(), game.maze());
+    other.play_demo(&mut assets, DemoPlayback::new(demo)).unwrap();
+    assert_eq!(other.maze(), game.maze());
+    other.run(&mut ScriptedPlatform::repeat(FrameInput::default(), 90)).unwrap();
+    assert_eq!(other.player.pos, game.player.pos);
+    assert_eq!(other.player.a.to_bits(), game.player.a.to_bits());
+}
+
+#[test]
+fn profiler_times_phases_and_exports_csv() {
+    let path = std::env::temp_dir().join(format!("ray_casting_profile_{}.csv", std::process::id()));
+    let mut config = Config::default();
+    config.debug.profiler = true;
+    config.debug.profile_csv = Some(path.clone());
+    let mut game = new_game_with(config);
+
+    let script = std::iter::once(action(Actions { toggle_view: true, ..Actions::default() }))
+        .chain(std::iter::repeat_n(forward(), 20));
+    let mut platform = ScriptedPlatform::new(script);
+    game.run(&mut platform).unwrap();
+
+    // El último cuadro queda abierto; el total de los demás es la espera del reloj virtual
+    let profiler = game.profiler();
+    assert_eq!(profiler.samples().count(), 20);
+    assert_eq!(profiler.frame_stats().avg, Duration::from_millis(16));
+    assert!(profiler.samples().all(|sample| sample.phase(Phase::Present) > Duration::ZERO));
+
+    let csv = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert!(csv.starts_with("phase,min_ms,avg_ms,p99_ms\nframe,16.000,16.000,16.000\n"), "{}", csv);
+    assert!(csv.contains("\nray_cast,"));
+    assert!(platform.messages.iter().any(|message| message.starts_with("Profile written to")));
+}
+
+#[test]
+fn level_change_crossfades_the_music_and_focus_loss_ducks_it() {
+    // Nivel con su propia lista de música; sin dispositivo los volúmenes evolucionan igual
+    let dir = std::env::temp_dir().join(format!("ray_casting_level_change_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let rooms = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/rooms.txt");
+    std::fs::copy(&rooms, dir.join("rooms.txt")).unwrap();
+    std::fs::write(dir.join("rooms.playlist"), "rooms.ogg\n").unwrap();
+
+    let mut game = new_game();
+    game.run(&mut ScriptedPlatform::repeat(FrameInput::default(), 150)).unwrap();
+    assert_eq!(game.audio.music_volumes(), vec![1.0]);
+
+    let mut assets = Assets::new(vec![PathBuf::from(env!("CARGO_MANIFEST_DIR"))]);
+    game.load_level(&mut assets, &dir.join("rooms.txt")).unwrap();
+    assert_eq!(game.maze(), &ray_casting::load_maze(rooms.to_str().unwrap()).unwrap());
+    assert!(game.load_level(&mut assets, &dir.join("missing.txt")).is_err());
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    // A mitad del fundido suenan las dos listas; al terminar queda solo la nueva
+    game.run(&mut ScriptedPlatform::repeat(FrameInput::default(), 60)).unwrap();
+    let volumes = game.audio.music_volumes();
+    assert_eq!(volumes.len(), 2, "volumes = {:?}", volumes);
+    assert!(volumes[0] > 0.0 && volumes[0] < 1.0 && volumes[1] > 0.0, "volumes = {:?}", volumes);
+    game.run(&mut ScriptedPlatform::repeat(FrameInput::default(), 150)).unwrap();
+    assert_eq!(game.audio.music_volumes(), vec![1.0]);
+
+    let mut unfocused = ScriptedPlatform::repeat(FrameInput::default(), 40);
+    unfocused.focused = false;
+    game.run(&mut unfocused).unwrap();
+    assert!((game.audio.music_volumes()[0] - 0.3).abs() < 1e-4, "{:?}", game.audio.music_volumes());
+    game.run(&mut ScriptedPlatform::repeat(FrameInput::default(), 40)).unwrap();
+    assert_eq!(game.audio.music_volumes(), vec![1.0]);
+}
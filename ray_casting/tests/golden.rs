@@ -0,0 +1,184 @@
+// Pruebas de regresión por imagen: renderiza poses fijas sobre los mapas de tests/fixtures y compara
+// el resultado con las referencias de tests/golden. Si falla, deja la imagen obtenida y un mapa de
+// diferencias en target/golden-diff. Con UPDATE_GOLDEN=1 se regeneran las referencias.
+use std::path::{Path, PathBuf};
+use nalgebra_glm::Vec2;
+use ray_casting::assets::checkerboard;
+use ray_casting::color::Color;
+use ray_casting::framebuffer::FrameBuffer;
+use ray_casting::maze::load_maze;
+use ray_casting::player::Player;
+use ray_casting::render::{render2d, render3d, render3d_threaded};
+
+const WIDTH: usize = 200;
+const HEIGHT: usize = 180;
+
+#[derive(Debug, Copy, Clone)]
+struct Tolerance {
+    // Diferencia máxima por canal para considerar iguales dos píxeles
+    pub channel: u8,
+    // Cantidad de píxeles que pueden superar esa diferencia
+    pub max_mismatched_pixels: usize,
+}
+
+impl Default for Tolerance {
+    fn default() -> Tolerance {
+        Tolerance { channel: 2, max_mismatched_pixels: 0 }
+    }
+}
+
+fn manifest_path(relative: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join(relative)
+}
+
+fn render_fixture(map: &str, pos: (f32, f32), angle: f32, mode_2d: bool) -> FrameBuffer {
+    render_player(map, Player::new(Vec2::new(pos.0, pos.1), angle), mode_2d)
+}
+
+fn render_player(map: &str, player: Player, mode_2d: bool) -> FrameBuffer {
+    let maze = load_maze(&manifest_path(&format!("tests/fixtures/{}", map)).to_string_lossy())
+        .expect("fixture map should load");
+
+    let mut framebuffer = FrameBuffer::new(WIDTH, HEIGHT);
+    framebuffer.clear();
+    if mode_2d {
+        render2d(&mut framebuffer, &maze, &player);
+    } else {
+        render3d(&mut framebuffer, &maze, &player, &checkerboard());
+    }
+    framebuffer
+}
+
+fn channel_difference(a: Color, b: Color) -> u8 {
+    a.r.abs_diff(b.r).max(a.g.abs_diff(b.g)).max(a.b.abs_diff(b.b))
+}
+
+// Compara con tests/golden/<name>.bmp y entra en pánico con un resumen si no coincide
+fn assert_matches_golden(name: &str, actual: &FrameBuffer, tolerance: Tolerance) {
+    let reference_path = manifest_path(&format!("tests/golden/{}.bmp", name));
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        actual.write_to_bmp(&reference_path).expect("reference image should be writable");
+        return;
+    }
+
+    let expected = FrameBuffer::from_bmp(&reference_path).unwrap_or_else(|e| {
+        panic!("missing reference {} ({}); run with UPDATE_GOLDEN=1 to create it", reference_path.display(), e)
+    });
+    assert_eq!(
+        (actual.width, actual.height),
+        (expected.width, expected.height),
+        "{}: size differs from the reference",
+        name
+    );
+
+    // Mapa de diferencias: la imagen obtenida atenuada con los píxeles distintos en magenta
+    let mut diff = FrameBuffer::new(actual.width, actual.height);
+    let mut mismatched = 0;
+    let mut worst = 0;
+    for y in 0..actual.height {
+        for x in 0..actual.width {
+            let (a, e) = (actual.get_color(x, y), expected.get_color(x, y));
+            let difference = channel_difference(a, e);
+            worst = worst.max(difference);
+            if difference > tolerance.channel {
+                mismatched += 1;
+                diff.set_pixel(x, y, Color::new(255, 0, 255));
+            } else {
+                diff.set_pixel(x, y, a * 0.3);
+            }
+        }
+    }
+
+    if mismatched > tolerance.max_mismatched_pixels {
+        let out_dir = manifest_path("target/golden-diff");
+        std::fs::create_dir_all(&out_dir).expect("diff directory should be writable");
+        let actual_path = out_dir.join(format!("{}-actual.bmp", name));
+        let diff_path = out_dir.join(format!("{}-diff.bmp", name));
+        actual.write_to_bmp(&actual_path).expect("actual image should be writable");
+        diff.write_to_bmp(&diff_path).expect("diff image should be writable");
+        panic!(
+            "{}: {} pixels differ by more than {} (worst {}); see {} and {}",
+            name,
+            mismatched,
+            tolerance.channel,
+            worst,
+            actual_path.display(),
+            diff_path.display()
+        );
+    }
+}
+
+#[test]
+fn render2d_corridor_overview() {
+    let frame = render_fixture("corridor.txt", (114.0, 114.0), 0.2, true);
+    assert_matches_golden("render2d_corridor_overview", &frame, Tolerance::default());
+}
+
+#[test]
+fn render2d_rooms_overview() {
+    let frame = render_fixture("rooms.txt", (150.0, 150.0), 0.8, true);
+    assert_matches_golden("render2d_rooms_overview", &frame, Tolerance::default());
+}
+
+#[test]
+fn render3d_corridor_looking_down_the_hall() {
+    let frame = render_fixture("corridor.txt", (114.0, 114.0), 0.0, false);
+    assert_matches_golden("render3d_corridor_hall", &frame, Tolerance::default());
+}
+
+#[test]
+fn render3d_corridor_facing_wall() {
+    let frame = render_fixture("corridor.txt", (600.0, 114.0), std::f32::consts::FRAC_PI_2 * 3.0, false);
+    assert_matches_golden("render3d_corridor_wall", &frame, Tolerance::default());
+}
+
+#[test]
+fn render3d_corridor_looking_up_while_crouched() {
+    let mut player = Player::new(Vec2::new(114.0, 114.0), 0.0);
+    player.pitch = 0.2;
+    player.eye_height = 0.3;
+    let frame = render_player("corridor.txt", player, false);
+    assert_matches_golden("render3d_corridor_pitch", &frame, Tolerance::default());
+}
+
+#[test]
+fn render3d_camera_inside_a_wall() {
+    // La esquina (0, 0) es pared: todos los rayos chocan a distancia 0
+    let mut player = Player::new(Vec2::new(0.0, 0.0), 0.0);
+    player.pitch = 0.3;
+    let frame = render_player("corridor.txt", player, false);
+    assert_matches_golden("render3d_inside_wall", &frame, Tolerance::default());
+}
+
+#[test]
+fn render3d_rooms_diagonal() {
+    let frame = render_fixture("rooms.txt", (150.0, 150.0), 0.8, false);
+    assert_matches_golden("render3d_rooms_diagonal", &frame, Tolerance::default());
+}
+
+#[test]
+fn render3d_threaded_matches_single_thread() {
+    let reference = render_fixture("rooms.txt", (150.0, 150.0), 0.8, false);
+    let maze = load_maze(&manifest_path("tests/fixtures/rooms.txt").to_string_lossy()).unwrap();
+    let player = Player::new(Vec2::new(150.0, 150.0), 0.8);
+    // Incluye cantidades que no dividen el ancho y más hilos que columnas útiles
+    for threads in [2, 3, 7, 64] {
+        let mut framebuffer = FrameBuffer::new(WIDTH, HEIGHT);
+        framebuffer.clear();
+        render3d_threaded(&mut framebuffer, &maze, &player, &checkerboard(), threads);
+        assert!(framebuffer.buffer == reference.buffer, "{} threads differ from one", threads);
+    }
+}
+
+#[test]
+fn render3d_fits_very_wide_and_tiny_frames() {
+    let maze = load_maze(&manifest_path("tests/fixtures/rooms.txt").to_string_lossy()).unwrap();
+    let player = Player::new(Vec2::new(150.0, 150.0), 0.8);
+    // Más anchos que altos de lo que cabe el minimapa, y de un píxel
+    for (width, height) in [(1000, 100), (2000, 10), (1, 1), (3, 1)] {
+        let mut framebuffer = FrameBuffer::new(width, height);
+        framebuffer.clear();
+        render3d(&mut framebuffer, &maze, &player, &checkerboard());
+    }
+}